@@ -0,0 +1,321 @@
+use crate::{engine::Direction, *};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone)]
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AchievementUnlocked>()
+            .add_systems(Startup, (load_achievements, spawn_toast_stack))
+            .add_systems(
+                Update,
+                (
+                    check_achievements
+                        .after(apply_move)
+                        .after(detect_milestones)
+                        .after(score_from_merge)
+                        .run_if(in_state(AppState::InGame))
+                        .run_if(not_replaying),
+                    spawn_toasts,
+                    fade_toasts,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Something to accomplish during a game
+#[derive(Debug, PartialEq, Eq)]
+pub struct Achievement {
+    /// Stable name under which the achievement is saved once unlocked
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: Condition,
+}
+
+/// What it takes to unlock an [`Achievement`], all of them are met within a single game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// Make a tile of `power`, without ever moving towards `without` if set
+    ReachTile {
+        power: u32,
+        without: Option<Direction>,
+    },
+    /// Reach the [`WinTarget`] in at most `moves` moves
+    WinWithin { moves: u32 },
+    /// Reach the [`WinTarget`] on a board of these dimensions
+    WinOnBoard { columns: usize, rows: usize },
+    /// Merge `pairs` pairs of tiles with a single move
+    MergesInOneMove { pairs: usize },
+    /// Score at least this many points
+    Score(u32),
+}
+
+/// Every achievement, in the order they're shown in the gallery
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "tile_2048",
+        name: "2048",
+        description: "Make a 2048 tile",
+        condition: Condition::ReachTile {
+            power: 11,
+            without: None,
+        },
+    },
+    Achievement {
+        id: "no_up_1024",
+        name: "Never look up",
+        description: "Make a 1024 tile without moving up",
+        condition: Condition::ReachTile {
+            power: 10,
+            without: Some(Direction::Up),
+        },
+    },
+    Achievement {
+        id: "quick_win",
+        name: "In a hurry",
+        description: "Win in under 900 moves",
+        condition: Condition::WinWithin { moves: 899 },
+    },
+    Achievement {
+        id: "four_pairs",
+        name: "Chain reaction",
+        description: "Merge four pairs of tiles in one move",
+        condition: Condition::MergesInOneMove { pairs: 4 },
+    },
+    Achievement {
+        id: "tiny_win",
+        name: "Tight squeeze",
+        description: "Win on a 3x3 board",
+        condition: Condition::WinOnBoard {
+            columns: 3,
+            rows: 3,
+        },
+    },
+    Achievement {
+        id: "score_20000",
+        name: "High roller",
+        description: "Score 20000 points in a single game",
+        condition: Condition::Score(20000),
+    },
+];
+
+/// An achievement was unlocked for the first time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct AchievementUnlocked(pub &'static Achievement);
+
+/// When each unlocked achievement was, in seconds since the Unix epoch, by id
+#[derive(Debug, Resource, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockedAchievements(HashMap<String, u64>);
+
+impl UnlockedAchievements {
+    /// Group under which the achievements of each profile are stored
+    const GROUP: &'static str = "achievements";
+
+    fn key(profile: &str) -> String {
+        format!("{}/{profile}", Self::GROUP)
+    }
+
+    /// Achievements unlocked by `profile`
+    ///
+    /// Achievements were once stored for everyone under the group's name, they now belong to the default profile.
+    pub fn load(profile: &str) -> Self {
+        storage::load(&Self::key(profile))
+            .or_else(|| {
+                (profile == Preferences::DEFAULT_PROFILE)
+                    .then(|| storage::load(Self::GROUP))
+                    .flatten()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn store(&self, profile: &str) {
+        storage::store(&Self::key(profile), self)
+    }
+
+    /// When `achievement` was unlocked, if it was
+    pub fn get(&self, achievement: &Achievement) -> Option<u64> {
+        self.0.get(achievement.id).copied()
+    }
+
+    pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.0.contains_key(achievement.id)
+    }
+
+    fn unlock(&mut self, achievement: &Achievement) {
+        self.0.insert(achievement.id.to_string(), unix_time());
+    }
+}
+
+/// Checks the conditions of the locked achievements against what happened during the move just played
+///
+/// Nothing is unlocked in assisted games, just like their scores don't make it to the leaderboard.
+fn check_achievements(
+    mut moves: EventReader<MoveApplied>,
+    mut merges: EventReader<TilesMerged>,
+    mut milestones: EventReader<MilestoneReached>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+    mut unlocks: EventWriter<AchievementUnlocked>,
+    game: Res<GameState>,
+    score: Res<Score>,
+    target: Res<WinTarget>,
+    recorder: Res<Recorder>,
+    history: Res<History>,
+    preferences: Res<Preferences>,
+) {
+    let Some(applied) = moves.read().last().copied() else {
+        return;
+    };
+    let pairs = merges.read().count();
+    let won = milestones
+        .read()
+        .any(|milestone| milestone.power == target.power);
+    //the events are read anyway, so that they aren't mistaken for those of a later move
+    if history.is_assisted() {
+        return;
+    }
+
+    //directions played so far, only looked up when needed since the whole recording must be read
+    let used = |direction: Direction| {
+        applied.direction == direction
+            || recorder
+                .replay(game.moves())
+                //without a recording we can't tell which directions were used
+                .is_none_or(|replay| replay.directions().any(|used| used == direction))
+    };
+
+    let mut newly_unlocked = false;
+    for achievement in ACHIEVEMENTS {
+        if unlocked.is_unlocked(achievement) {
+            continue;
+        }
+        let met = match achievement.condition {
+            Condition::ReachTile { power, without } => {
+                game.highest_power() as u32 >= power && !without.is_some_and(used)
+            }
+            Condition::WinWithin { moves } => won && game.moves() <= moves,
+            Condition::WinOnBoard { columns, rows } => {
+                won && *game.board() == Board { columns, rows }
+            }
+            Condition::MergesInOneMove { pairs: needed } => pairs >= needed,
+            Condition::Score(needed) => score.0 >= needed,
+        };
+        if met {
+            info!("Achievement unlocked: {}", achievement.name);
+            unlocked.unlock(achievement);
+            unlocks.send(AchievementUnlocked(achievement));
+            newly_unlocked = true;
+        }
+    }
+    if newly_unlocked {
+        unlocked.store(&preferences.profile);
+    }
+}
+
+fn load_achievements(mut commands: Commands, preferences: Res<Preferences>) {
+    commands.insert_resource(UnlockedAchievements::load(&preferences.profile))
+}
+
+/// Seconds a toast stays on screen
+const TOAST_DURATION: f32 = 4.0;
+
+/// Column in the bottom right corner of the window where toasts pile up
+#[derive(Debug, Component)]
+struct ToastStack;
+
+/// Tells the player an achievement was unlocked, vanishes after a while
+#[derive(Debug, Component)]
+struct Toast {
+    /// Seconds since it was shown
+    elapsed: f32,
+}
+
+fn spawn_toast_stack(mut commands: Commands) {
+    commands.spawn((
+        ToastStack,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(8.0),
+                bottom: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            //above the menus
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_toasts(
+    mut commands: Commands,
+    mut unlocks: EventReader<AchievementUnlocked>,
+    stack: Query<Entity, With<ToastStack>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok(stack) = stack.get_single() else {
+        return;
+    };
+    for AchievementUnlocked(achievement) in unlocks.read() {
+        let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+        let toast = commands
+            .spawn((
+                Toast { elapsed: 0.0 },
+                TextBundle {
+                    text: Text::from_sections([
+                        TextSection::new(
+                            format!("🏆 {}\n", achievement.name),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::hex("edc22e").unwrap(),
+                            },
+                        ),
+                        TextSection::new(
+                            achievement.description,
+                            TextStyle {
+                                font,
+                                font_size: 18.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                    ]),
+                    style: Style {
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.47, 0.43, 0.4, 0.9).into(),
+                    ..default()
+                },
+            ))
+            .id();
+        commands.entity(stack).add_child(toast);
+    }
+}
+
+fn fade_toasts(
+    mut commands: Commands,
+    mut toasts: Query<(Entity, &mut Toast, &mut BackgroundColor, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut toast, mut background, mut text) in &mut toasts {
+        toast.elapsed += time.delta_seconds();
+        if toast.elapsed >= TOAST_DURATION {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        //fading out during the last second
+        let alpha = (TOAST_DURATION - toast.elapsed).min(1.0);
+        background.0.set_a(0.9 * alpha);
+        for section in &mut text.sections {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
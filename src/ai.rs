@@ -0,0 +1,354 @@
+//! A player looking for the best move with an expectimax search
+//!
+//! The search itself only relies on the [`engine`] and on bevy's [`Instant`], so that it can be used without running
+//! the game. Unlike the one from `std`, that clock also works in browsers. In game, searches run on the
+//! [`AsyncComputeTaskPool`] so that frames keep being drawn while the AI thinks.
+
+use crate::{
+    engine::{Direction, MoveTracker},
+    *,
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, AsyncComputeTaskPool, Task},
+    utils::Instant,
+};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone)]
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AiPlayer>()
+            .add_systems(OnEnter(AppState::Setup), stop_ai)
+            .add_systems(
+                Update,
+                (
+                    apply_ai_preferences.run_if(resource_changed::<Preferences>()),
+                    (toggle_ai_with_keybind, play_with_ai.in_set(MoveInput))
+                        .chain()
+                        .run_if(in_state(AppState::InGame))
+                        .run_if(not_replaying),
+                ),
+            );
+    }
+}
+
+/// Value of a board on which no move can be made anymore
+const LOST: f32 = -10_000.0;
+
+/// Chance branches less likely than this aren't searched further, their board is evaluated right away
+const CUTOFF: f32 = 0.0001;
+
+/// Looks for the move with the best expected outcome, taking the odds of each tile spawning into account
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Expectimax {
+    /// Number of moves looked ahead at most
+    pub depth: u32,
+    /// Time after which the search stops looking further ahead, a search one move ahead is always completed
+    pub time_budget: Duration,
+}
+
+impl Default for Expectimax {
+    fn default() -> Self {
+        Expectimax {
+            depth: 4,
+            time_budget: Duration::from_millis(50),
+        }
+    }
+}
+
+impl Expectimax {
+    /// The move with the best expected value, `None` if no move changes the board
+    pub fn best_move(&self, game: &GameState) -> Option<Direction> {
+        self.evaluate(game)
+            .into_iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(direction, _)| direction)
+    }
+
+    /// Expected value of each move which changes the board
+    ///
+    /// The search goes one move further each time, until the time budget or the depth is exhausted. The values come
+    /// from the deepest search which could be completed.
+    pub fn evaluate(&self, game: &GameState) -> Vec<(Direction, f32)> {
+        let deadline = Instant::now() + self.time_budget;
+        let search = Search::new(game);
+        let mut values = Vec::new();
+        for depth in 1..=self.depth.max(1) {
+            //the first search always completes so that there is a move to play
+            let deadline = (depth > 1).then_some(deadline);
+            match search.root(game.cells(), depth, deadline) {
+                Some(deeper) => values = deeper,
+                None => break,
+            }
+        }
+        values
+    }
+}
+
+/// The board and the odds of each tile spawning, shared by the nodes of a search
+struct Search<'a> {
+    board: &'a Board,
+    /// (`power`, `probability`) of each tile which can spawn
+    spawns: Vec<(usize, f32)>,
+}
+
+impl<'a> Search<'a> {
+    fn new(game: &'a GameState) -> Self {
+        let values = &game.rules().values;
+        let total: usize = values.iter().map(|(_, weight)| weight).sum();
+        Search {
+            board: game.board(),
+            spawns: values
+                .iter()
+                .map(|(power, weight)| (*power, *weight as f32 / total.max(1) as f32))
+                .collect(),
+        }
+    }
+
+    /// The cells after moving towards `direction`, `None` if nothing moved
+    fn slide(&self, cells: &[usize], direction: Direction) -> Option<Vec<usize>> {
+        let mut tracker = MoveTracker::new(self.board.clone(), cells.to_vec());
+        tracker
+            .apply(direction)
+            .changed
+            .then(|| tracker.into_cells())
+    }
+
+    /// Value of each move from `cells`, `None` if the search ran past the deadline
+    fn root(
+        &self,
+        cells: &[usize],
+        depth: u32,
+        deadline: Option<Instant>,
+    ) -> Option<Vec<(Direction, f32)>> {
+        let mut values = Vec::new();
+        for direction in Direction::ALL {
+            if let Some(after) = self.slide(cells, direction) {
+                values.push((direction, self.chance(&after, depth, 1.0, deadline)?));
+            }
+        }
+        Some(values)
+    }
+
+    /// Value of the best move from `cells`
+    fn max(
+        &self,
+        cells: &[usize],
+        depth: u32,
+        probability: f32,
+        deadline: Option<Instant>,
+    ) -> Option<f32> {
+        if depth == 0 {
+            return Some(heuristic(self.board, cells));
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return None;
+        }
+        let mut best = None;
+        for direction in Direction::ALL {
+            if let Some(after) = self.slide(cells, direction) {
+                let value = self.chance(&after, depth, probability, deadline)?;
+                best = Some(best.map_or(value, |best: f32| best.max(value)));
+            }
+        }
+        Some(best.unwrap_or(LOST))
+    }
+
+    /// Expected value of `cells` once a tile spawned on them
+    ///
+    /// When several tiles spawn after each move only one of them is taken into account, which keeps the search
+    /// manageable.
+    fn chance(
+        &self,
+        cells: &[usize],
+        depth: u32,
+        probability: f32,
+        deadline: Option<Instant>,
+    ) -> Option<f32> {
+        let empty: Vec<_> = (0..cells.len()).filter(|idx| cells[*idx] == 0).collect();
+        if empty.is_empty() {
+            return self.max(cells, depth - 1, probability, deadline);
+        }
+        let mut cells = cells.to_vec();
+        let mut expected = 0.0;
+        for &cell in &empty {
+            for &(power, odds) in &self.spawns {
+                let odds = odds / empty.len() as f32;
+                cells[cell] = power;
+                let value = if probability * odds < CUTOFF {
+                    heuristic(self.board, &cells)
+                } else {
+                    self.max(&cells, depth - 1, probability * odds, deadline)?
+                };
+                expected += odds * value;
+            }
+            cells[cell] = 0;
+        }
+        Some(expected)
+    }
+}
+
+/// How promising a board is, the higher the better
+///
+/// Boards with many empty cells, tiles sorted along the rows and columns, neighbours of close values and the largest
+/// tile in a corner are favoured. Values are compared as powers, not as the tiles' values.
+pub fn heuristic(board: &Board, cells: &[usize]) -> f32 {
+    let rows = (0..board.rows).map(|row| {
+        (0..board.columns)
+            .map(|col| cells[row * board.columns + col] as f32)
+            .collect::<Vec<_>>()
+    });
+    let columns = (0..board.columns).map(|col| {
+        (0..board.rows)
+            .map(|row| cells[row * board.columns + col] as f32)
+            .collect::<Vec<_>>()
+    });
+
+    let mut monotonicity = 0.0;
+    let mut smoothness = 0.0;
+    let mut merges = 0.0;
+    for line in rows.chain(columns) {
+        let (mut increasing, mut decreasing) = (0.0, 0.0);
+        for pair in line.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if a > b {
+                decreasing += a - b;
+            } else {
+                increasing += b - a;
+            }
+        }
+        //a line sorted either way isn't penalized
+        monotonicity -= f32::min(increasing, decreasing);
+
+        let tiles: Vec<_> = line.into_iter().filter(|power| *power > 0.0).collect();
+        for pair in tiles.windows(2) {
+            smoothness -= (pair[0] - pair[1]).abs();
+            if pair[0] == pair[1] {
+                merges += 1.0;
+            }
+        }
+    }
+
+    let empty = cells.iter().filter(|power| **power == 0).count() as f32;
+    let highest = cells.iter().copied().max().unwrap_or_default();
+    let corners = [
+        0,
+        board.columns - 1,
+        board.size() - board.columns,
+        board.size() - 1,
+    ];
+    let cornered = if corners.iter().any(|corner| cells[*corner] == highest) {
+        highest as f32
+    } else {
+        0.0
+    };
+
+    2.7 * empty + monotonicity + 0.1 * smoothness + 0.7 * merges + cornered
+}
+
+/// A search running in the background, along with the board it started from
+#[derive(Debug)]
+pub struct PendingSearch<T> {
+    task: Task<T>,
+    cells: Vec<usize>,
+}
+
+impl<T: Send + 'static> PendingSearch<T> {
+    /// Runs `search` on a copy of `game` on the [`AsyncComputeTaskPool`]
+    pub fn spawn(game: &GameState, search: impl FnOnce(&GameState) -> T + Send + 'static) -> Self {
+        let cells = game.cells().to_vec();
+        let game = game.clone();
+        PendingSearch {
+            task: AsyncComputeTaskPool::get().spawn(async move { search(&game) }),
+            cells,
+        }
+    }
+
+    /// The outcome of the search once it's over
+    pub fn poll(&mut self) -> Option<T> {
+        self.task.is_finished().then(|| block_on(&mut self.task))
+    }
+
+    /// Whether the search was started from the board `game` is on, its outcome is stale otherwise
+    pub fn started_from(&self, game: &GameState) -> bool {
+        self.cells == game.cells()
+    }
+}
+
+/// Whether the AI plays instead of the player, and how hard it thinks
+#[derive(Debug, Resource, Default)]
+pub struct AiPlayer {
+    pub enabled: bool,
+    pub search: Expectimax,
+    /// Search for the next move, if the AI is thinking
+    pending: Option<PendingSearch<Option<Direction>>>,
+}
+
+fn apply_ai_preferences(preferences: Res<Preferences>, mut ai: ResMut<AiPlayer>) {
+    ai.search = Expectimax {
+        depth: preferences.ai_depth.max(1),
+        time_budget: Duration::from_secs_f32(preferences.ai_time_budget.max(0.0)),
+    };
+}
+
+/// Each game starts with the player in control
+fn stop_ai(mut ai: ResMut<AiPlayer>) {
+    ai.enabled = false;
+    ai.pending = None;
+}
+
+fn toggle_ai_with_keybind(
+    keys: Res<Input<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut ai: ResMut<AiPlayer>,
+) {
+    if keybinds.just_pressed(Action::ToggleAi, &keys) {
+        ai.enabled = !ai.enabled;
+        info!("AI {}", if ai.enabled { "playing" } else { "stopped" });
+    }
+}
+
+/// Sends the move the AI picked, thinking only once the tiles stopped sliding so that the player can follow the game
+///
+/// Moves found for a board which changed in the meantime, for instance because the player undid a move, are dropped.
+fn play_with_ai(
+    mut ai: ResMut<AiPlayer>,
+    queue: Res<MoveQueue>,
+    game: Res<GameState>,
+    preferences: Res<Preferences>,
+    tiles: Query<&TileAnimation>,
+    mut history: ResMut<History>,
+    mut directions: EventWriter<Direction>,
+) {
+    if !ai.enabled {
+        ai.pending = None;
+        return;
+    }
+    if let Some(pending) = &mut ai.pending {
+        let Some(best) = pending.poll() else {
+            return;
+        };
+        let current = pending.started_from(&game);
+        ai.pending = None;
+        if let Some(direction) = best.filter(|_| current) {
+            history.record_ai_move();
+            directions.send(direction);
+        }
+        return;
+    }
+    if !queue.0.is_empty()
+        || tiles
+            .iter()
+            .any(|animation| animation.is_sliding(preferences.animation_duration))
+    {
+        return;
+    }
+    let search = ai.search;
+    ai.pending = Some(PendingSearch::spawn(&game, move |game| {
+        search.best_move(game)
+    }));
+}
@@ -0,0 +1,120 @@
+use crate::*;
+
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+#[derive(Debug, Copy, Clone)]
+pub struct TileAnimationPlugin;
+
+impl Plugin for TileAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            animate_tiles
+                .after(apply_move)
+                .after(move_tiles)
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+/// Tween played on a tile, removed once over
+///
+/// Tiles first slide to their new cell, after which merged tiles pop and new ones grow in. Each step lasts for the
+/// [`Preferences::animation_duration`].
+#[derive(Debug, Component, Clone, Copy, PartialEq)]
+pub struct TileAnimation {
+    /// Where the tile slides from, if it moved
+    slide_from: Option<Vec3>,
+    effect: TileEffect,
+    /// Seconds since the animation started
+    elapsed: f32,
+}
+
+/// What happens to a tile once it stopped sliding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileEffect {
+    None,
+    /// The tile was merged into
+    Pop,
+    /// The tile was just spawned
+    Grow,
+    /// The tile merged into another one, it's despawned once it reached it
+    Vanish,
+}
+
+impl TileAnimation {
+    pub fn new(slide_from: Option<Vec3>, effect: TileEffect) -> Self {
+        TileAnimation {
+            slide_from,
+            effect,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Whether the tiles are still moving, the next move waits for them
+    ///
+    /// New tiles count as well since they're yet to appear.
+    pub fn is_sliding(&self, duration: f32) -> bool {
+        self.elapsed < duration
+    }
+
+    /// Whether the tile is only shown until it reached the tile it merged into
+    pub fn is_vanishing(&self) -> bool {
+        self.effect == TileEffect::Vanish
+    }
+}
+
+/// Eases the animations' progress, `t` going from 0 to 1
+fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(2)
+}
+
+fn animate_tiles(
+    mut commands: Commands,
+    mut tiles: Query<(Entity, &Position, &mut Transform, &mut TileAnimation)>,
+    tiling: Res<Tiling>,
+    board: Res<Board>,
+    preferences: Res<Preferences>,
+    time: Res<Time>,
+) {
+    let duration = preferences.animation_duration;
+    for (entity, position, mut transform, mut animation) in &mut tiles {
+        animation.elapsed += time.delta_seconds();
+        //progress of the slide, then of the effect
+        let (slide, effect) = if duration > 0.0 {
+            (
+                (animation.elapsed / duration).min(1.0),
+                (animation.elapsed / duration - 1.0).clamp(0.0, 1.0),
+            )
+        } else {
+            (1.0, 1.0)
+        };
+
+        let target = position.to_translation(&tiling, &board);
+        transform.translation = match animation.slide_from {
+            Some(from) => from.lerp(target, ease_out(slide)),
+            None => target,
+        };
+        transform.scale = tiling.scale()
+            * match animation.effect {
+                TileEffect::None | TileEffect::Vanish => 1.0,
+                TileEffect::Pop => 1.0 + 0.2 * (effect * PI).sin(),
+                TileEffect::Grow => ease_out(effect),
+            };
+
+        let over = match animation.effect {
+            TileEffect::None | TileEffect::Vanish => slide >= 1.0,
+            TileEffect::Pop | TileEffect::Grow => effect >= 1.0,
+        };
+        match animation.effect {
+            TileEffect::Vanish if over => commands.entity(entity).despawn_recursive(),
+            _ if over => {
+                commands.entity(entity).remove::<TileAnimation>();
+            }
+            //merged tiles are drawn over the ones vanishing under them
+            TileEffect::Pop => transform.translation.z = 1.0,
+            _ => (),
+        }
+    }
+}
@@ -5,12 +5,17 @@ use bevy::{asset::LoadState, prelude::*};
 pub struct TileHandles(pub Vec<Handle<Image>>);
 
 impl TileHandles {
-    const TILES: [u32; 14] = [
+    pub(crate) const TILES: [u32; 14] = [
         0, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192,
     ];
     fn paths() -> impl IntoIterator<Item = String> {
         Self::TILES.map(|int| format!("tiles/{int}.png"))
     }
+
+    /// Texture of the tile of `power`, tiles bigger than the last texture (which larger boards can reach) reuse it
+    pub fn tile(&self, power: usize) -> &Handle<Image> {
+        &self.0[power.min(self.0.len() - 1)]
+    }
 }
 
 #[derive(Debug, Resource, Default)]
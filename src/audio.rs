@@ -1,16 +1,20 @@
-use bevy::{audio::PlaybackMode, prelude::*};
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
 
-use crate::AppState;
+use crate::{AppState, Preferences};
 
 #[derive(Debug, Component)]
 struct BackgroundMusic;
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, preferences: Res<Preferences>) {
     commands.spawn((
         AudioBundle {
             source: asset_server.load("audio/music.ogg"),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Loop,
+                volume: Volume::new_absolute(preferences.music_volume),
                 ..default()
             },
         },
@@ -18,11 +22,24 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
+/// Keeps the music at the volume chosen in the settings
+fn apply_volume(
+    music: Query<Ref<AudioSink>, With<BackgroundMusic>>,
+    preferences: Res<Preferences>,
+) {
+    for sink in &music {
+        if sink.is_added() || preferences.is_changed() {
+            sink.set_volume(preferences.music_volume)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct MusicPlugin;
 
 impl Plugin for MusicPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Loading), setup);
+        app.add_systems(OnEnter(AppState::Loading), setup)
+            .add_systems(Update, apply_volume);
     }
 }
@@ -0,0 +1,331 @@
+//! Plays many games without a window and prints how a strategy fared
+//!
+//! `b2048-sim --strategy expectimax --seeds 0..100 --board 4x4 --rules Classic --json`
+//!
+//! `b2048-sim --values 1:9,2:1 --start 2 --per-move 1` plays with custom spawn rules.
+
+use b2048::{
+    engine::{Board, Direction, GameRng, GameState, SpawnRules},
+    Expectimax,
+};
+use std::{collections::BTreeMap, process::ExitCode, time::Instant};
+
+const USAGE: &str = "\
+Usage: b2048-sim [options]
+
+Options:
+  --strategy <name>   random, greedy, expectimax or corner (default: greedy)
+  --seeds <a>..<b>    seeds of the games played, from a included to b excluded (default: 0..100)
+  --board <C>x<R>     columns and rows of the board (default: 4x4)
+  --rules <name>      spawn rules: Relaxed, Classic or Hectic (default: Classic)
+  --values <p:w,...>  powers of the tiles which can spawn and their weights, such as 1:9,2:1 (default: from the rules)
+  --start <n>         tiles on the board when a game starts (default: from the rules)
+  --per-move <n>      tiles spawned after each move (default: from the rules)
+  --depth <n>         moves the expectimax strategy looks ahead (default: 4)
+  --time-ms <n>       milliseconds the expectimax strategy may think per move (default: 50)
+  --json              prints the statistics as JSON instead of a table
+  --help              prints this message";
+
+/// How moves are picked
+#[derive(Debug, Clone, Copy)]
+enum Strategy {
+    /// Any move which changes the board
+    Random,
+    /// The move scoring the most right away
+    Greedy,
+    Expectimax(Expectimax),
+    /// Keeps the largest tiles in the bottom left corner, moving down or left whenever possible
+    Corner,
+}
+
+impl Strategy {
+    fn pick(&self, game: &GameState, rng: &mut GameRng) -> Option<Direction> {
+        let legal = game.legal_moves();
+        match self {
+            Strategy::Random => (!legal.is_empty()).then(|| legal[rng.below(legal.len())]),
+            Strategy::Greedy => legal.into_iter().max_by_key(|direction| {
+                let mut after = game.clone();
+                after.apply(*direction);
+                //among moves scoring the same, the one leaving the most room is better
+                (after.score(), after.empty_cells().count())
+            }),
+            Strategy::Expectimax(search) => search.best_move(game),
+            Strategy::Corner => [
+                Direction::Down,
+                Direction::Left,
+                Direction::Right,
+                Direction::Up,
+            ]
+            .into_iter()
+            .find(|direction| legal.contains(direction)),
+        }
+    }
+}
+
+/// What the command line asked for
+struct Options {
+    strategy: Strategy,
+    seeds: std::ops::Range<u64>,
+    board: Board,
+    rules: SpawnRules,
+    json: bool,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut strategy = "greedy".to_string();
+    let mut search = Expectimax::default();
+    //applied over the rules, whichever order they're given in
+    let (mut values, mut starting_tiles, mut tiles_per_move) = (None, None, None);
+    let mut options = Options {
+        strategy: Strategy::Greedy,
+        seeds: 0..100,
+        board: Board::default(),
+        rules: SpawnRules::default(),
+        json: false,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("`{arg}` expects a value"));
+        match arg.as_str() {
+            "--strategy" => strategy = value()?,
+            "--seeds" => {
+                let value = value()?;
+                let (start, end) = value
+                    .split_once("..")
+                    .ok_or(format!("invalid seed range `{value}`, expected <a>..<b>"))?;
+                let parse = |seed: &str| {
+                    seed.parse::<u64>()
+                        .map_err(|_| format!("invalid seed `{seed}`"))
+                };
+                options.seeds = parse(start)?..parse(end)?;
+            }
+            "--board" => {
+                let value = value()?;
+                let (columns, rows) = value
+                    .split_once('x')
+                    .and_then(|(columns, rows)| Some((columns.parse().ok()?, rows.parse().ok()?)))
+                    .filter(|(columns, rows)| *columns > 0 && *rows > 0)
+                    .ok_or(format!(
+                        "invalid board `{value}`, expected <columns>x<rows>"
+                    ))?;
+                options.board = Board { columns, rows };
+            }
+            "--rules" => {
+                let value = value()?;
+                options.rules = SpawnRules::presets()
+                    .into_iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&value))
+                    .map(|(_, rules)| rules)
+                    .ok_or(format!("unknown rules `{value}`"))?;
+            }
+            "--values" => {
+                let value = value()?;
+                let parse = |pair: &str| {
+                    let (power, weight) = pair.split_once(':')?;
+                    Some((power.parse().ok()?, weight.parse().ok()?))
+                };
+                values = Some(
+                    value
+                        .split(',')
+                        .map(|pair| {
+                            parse(pair)
+                                .ok_or(format!("invalid tile `{pair}`, expected <power>:<weight>"))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+            "--start" => {
+                starting_tiles = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "`--start` expects a number of tiles".to_string())?,
+                )
+            }
+            "--per-move" => {
+                tiles_per_move = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "`--per-move` expects a number of tiles".to_string())?,
+                )
+            }
+            "--depth" => {
+                search.depth = value()?
+                    .parse()
+                    .ok()
+                    .filter(|depth| *depth > 0)
+                    .ok_or("`--depth` expects a positive integer".to_string())?
+            }
+            "--time-ms" => {
+                search.time_budget = std::time::Duration::from_millis(
+                    value()?
+                        .parse()
+                        .map_err(|_| "`--time-ms` expects a positive integer".to_string())?,
+                )
+            }
+            "--json" => options.json = true,
+            "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0)
+            }
+            _ => return Err(format!("unknown option `{arg}`\n\n{USAGE}")),
+        }
+    }
+
+    options.strategy = match strategy.to_lowercase().as_str() {
+        "random" => Strategy::Random,
+        "greedy" => Strategy::Greedy,
+        "expectimax" => Strategy::Expectimax(search),
+        "corner" => Strategy::Corner,
+        _ => return Err(format!("unknown strategy `{strategy}`")),
+    };
+    if let Some(values) = values {
+        options.rules.values = values;
+    }
+    if let Some(starting_tiles) = starting_tiles {
+        options.rules.starting_tiles = starting_tiles;
+    }
+    if let Some(tiles_per_move) = tiles_per_move {
+        options.rules.tiles_per_move = tiles_per_move;
+    }
+    if options.seeds.is_empty() {
+        return Err("the seed range is empty".to_string());
+    }
+    options
+        .rules
+        .validate(&options.board)
+        .map_err(|err| format!("invalid rules: {err}"))?;
+    Ok(options)
+}
+
+/// Score and largest tile of a game played to the end
+struct Outcome {
+    score: u32,
+    highest_power: usize,
+}
+
+fn play(options: &Options, seed: u64) -> Outcome {
+    let mut game =
+        GameState::with_seed(options.board.clone(), seed).with_rules(options.rules.clone());
+    //the random strategy gets its own generator, so that the tiles spawned don't depend on it
+    let mut rng = GameRng::from_seed(!seed);
+    game.start();
+    while let Some(direction) = options.strategy.pick(&game, &mut rng) {
+        game.play(direction);
+    }
+    Outcome {
+        score: game.score(),
+        highest_power: game.highest_power(),
+    }
+}
+
+/// Aggregated outcomes of all the games
+struct Report {
+    games: usize,
+    seconds: f64,
+    mean: f64,
+    /// (`percentile`, `score`)
+    percentiles: Vec<(u32, u32)>,
+    /// Number of games for each value of their largest tile
+    highest_tiles: BTreeMap<u64, usize>,
+}
+
+impl Report {
+    const PERCENTILES: [u32; 7] = [0, 10, 25, 50, 75, 90, 100];
+
+    fn new(outcomes: &[Outcome], seconds: f64) -> Self {
+        let mut scores: Vec<_> = outcomes.iter().map(|outcome| outcome.score).collect();
+        scores.sort_unstable();
+        //nearest rank, the 0th percentile being the lowest score
+        let percentile = |p: u32| {
+            let rank = (p as usize * scores.len()).div_ceil(100);
+            scores[rank.saturating_sub(1)]
+        };
+        let mut highest_tiles = BTreeMap::new();
+        for outcome in outcomes {
+            *highest_tiles
+                .entry(2u64.pow(outcome.highest_power as u32))
+                .or_default() += 1;
+        }
+        Report {
+            games: outcomes.len(),
+            seconds,
+            mean: scores.iter().map(|score| *score as f64).sum::<f64>() / scores.len() as f64,
+            percentiles: Self::PERCENTILES
+                .into_iter()
+                .map(|p| (p, percentile(p)))
+                .collect(),
+            highest_tiles,
+        }
+    }
+
+    fn games_per_second(&self) -> f64 {
+        self.games as f64 / self.seconds.max(f64::EPSILON)
+    }
+
+    fn print_table(&self) {
+        println!("Games played  {:>10}", self.games);
+        println!("Games/sec     {:>10.1}", self.games_per_second());
+        println!("Mean score    {:>10.1}", self.mean);
+        println!();
+        println!("Percentile         Score");
+        for (p, score) in &self.percentiles {
+            println!("{:>10} {:>13}", format!("p{p}"), score);
+        }
+        println!();
+        println!("Largest tile  Games      Share");
+        for (tile, games) in self.highest_tiles.iter().rev() {
+            println!(
+                "{tile:>12} {games:>6} {:>9.1}%",
+                100.0 * *games as f64 / self.games as f64
+            );
+        }
+    }
+
+    fn print_json(&self) {
+        let percentiles: Vec<_> = self
+            .percentiles
+            .iter()
+            .map(|(p, score)| format!("\"p{p}\": {score}"))
+            .collect();
+        let highest_tiles: Vec<_> = self
+            .highest_tiles
+            .iter()
+            .map(|(tile, games)| format!("\"{tile}\": {games}"))
+            .collect();
+        println!(
+            "{{\"games\": {}, \"games_per_second\": {:.3}, \"mean_score\": {:.3}, \"percentiles\": {{{}}}, \
+             \"highest_tiles\": {{{}}}}}",
+            self.games,
+            self.games_per_second(),
+            self.mean,
+            percentiles.join(", "),
+            highest_tiles.join(", ")
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let start = Instant::now();
+    let outcomes: Vec<_> = options
+        .seeds
+        .clone()
+        .map(|seed| play(&options, seed))
+        .collect();
+    let report = Report::new(&outcomes, start.elapsed().as_secs_f64());
+
+    if options.json {
+        report.print_json()
+    } else {
+        report.print_table()
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,504 @@
+//! Bevy-free implementation of the game rules.
+//!
+//! [`GameState`] is the source of truth for a game: the ECS systems in [`crate::tiling`] and [`crate::moving`] only
+//! mirror it with entities. Nothing in here depends on a window, a renderer or an `App`, so bots, tests and tools can
+//! play games directly.
+
+use serde::{Deserialize, Serialize};
+
+pub use tracker::{MoveTracker, TileMerge, TileSlide};
+
+/// Dimensions of the board
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Board {
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board {
+            columns: 4,
+            rows: 4,
+        }
+    }
+}
+
+impl Board {
+    /// Number of cells on the board
+    pub fn size(&self) -> usize {
+        self.columns * self.rows
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Left,
+    Up,
+    Right,
+    Down,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+    ];
+}
+
+/// How new tiles appear on the board
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpawnRules {
+    /// (`power`, `weight`) of the tiles which can spawn, a tile's odds are its weight over the sum of all of them
+    pub values: Vec<(usize, usize)>,
+    /// Number of tiles on the board when a game starts
+    pub starting_tiles: usize,
+    /// Number of tiles spawned after each move
+    pub tiles_per_move: usize,
+}
+
+impl Default for SpawnRules {
+    fn default() -> Self {
+        SpawnRules {
+            values: vec![(1, 7), (2, 3)],
+            starting_tiles: 2,
+            tiles_per_move: 1,
+        }
+    }
+}
+
+impl SpawnRules {
+    /// Named variants of the rules, from easiest to hardest
+    pub fn presets() -> [(&'static str, SpawnRules); 3] {
+        [
+            (
+                "Relaxed",
+                SpawnRules {
+                    values: vec![(1, 5), (2, 4), (3, 1)],
+                    ..SpawnRules::default()
+                },
+            ),
+            ("Classic", SpawnRules::default()),
+            (
+                "Hectic",
+                SpawnRules {
+                    values: vec![(1, 9), (2, 1)],
+                    starting_tiles: 3,
+                    tiles_per_move: 2,
+                },
+            ),
+        ]
+    }
+
+    /// Checks that games can be played with these rules on `board`, telling what's wrong otherwise
+    pub fn validate(&self, board: &Board) -> Result<(), String> {
+        if self.values.is_empty() {
+            return Err("no tile can spawn".to_string());
+        }
+        if self.values.iter().any(|(power, _)| *power == 0) {
+            return Err("tiles can't have a power of 0, they would be empty cells".to_string());
+        }
+        if self.values.iter().all(|(_, weight)| *weight == 0) {
+            return Err("the weights of the tiles add up to 0".to_string());
+        }
+        if self.starting_tiles > board.size() {
+            return Err(format!(
+                "{} starting tiles don't fit on a {}x{} board",
+                self.starting_tiles, board.columns, board.rows
+            ));
+        }
+        Ok(())
+    }
+
+    /// Name of the preset these rules correspond to, if any
+    pub fn preset_name(&self) -> Option<&'static str> {
+        SpawnRules::presets()
+            .into_iter()
+            .find(|(_, rules)| rules == self)
+            .map(|(name, _)| name)
+    }
+}
+
+/// Pseudo-random number generator (SplitMix64) through which every random decision of a game goes
+///
+/// We don't rely on `rand`'s generators and distributions because their output may differ between versions and
+/// platforms: a game must always play out the same from a given seed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng { seed, state: seed }
+    }
+
+    pub fn from_entropy() -> Self {
+        GameRng::from_seed(rand::random())
+    }
+
+    /// The seed this generator was created from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly picks a number in `0..bound`
+    ///
+    /// `bound` must not be zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+/// What happened to the board when a [`Direction`] was applied to it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveOutcome {
+    /// Whether any tile moved or merged
+    pub changed: bool,
+    /// Powers of the tiles that were merged, before the merge
+    pub merged: Vec<u32>,
+    /// Where each tile on the board before the move went, including those which didn't move
+    pub slides: Vec<TileSlide>,
+    /// Which tiles merged into which
+    pub merges: Vec<TileMerge>,
+}
+
+/// A game in progress
+///
+/// Cells are stored as row-major indices holding the power of two of the tile they contain, zero meaning the cell is
+/// empty. This is the same layout as [`crate::Position`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    board: Board,
+    cells: Vec<usize>,
+    score: u32,
+    /// Number of moves which changed the board
+    moves: u32,
+    rng: GameRng,
+    #[serde(default)]
+    rules: SpawnRules,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState::new(Board::default())
+    }
+}
+
+impl GameState {
+    /// Creates an empty game for `board` with a random seed
+    pub fn new(board: Board) -> Self {
+        GameState::with_rng(board, GameRng::from_entropy())
+    }
+
+    /// Creates an empty game for `board` which will always play out the same for the same moves
+    pub fn with_seed(board: Board, seed: u64) -> Self {
+        GameState::with_rng(board, GameRng::from_seed(seed))
+    }
+
+    fn with_rng(board: Board, rng: GameRng) -> Self {
+        GameState {
+            cells: vec![0; board.size()],
+            board,
+            score: 0,
+            moves: 0,
+            rng,
+            rules: SpawnRules::default(),
+        }
+    }
+
+    /// Changes the way tiles spawn during this game
+    pub fn with_rules(mut self, rules: SpawnRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn rules(&self) -> &SpawnRules {
+        &self.rules
+    }
+
+    /// Seed of the game, enough to replay it given the same moves
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The power held by each cell, zero for empty cells
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Number of moves which changed the board so far
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Iterator of (`index`, `power`) for all occupied cells
+    pub fn tiles(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, power)| *power != 0)
+    }
+
+    /// Power of the biggest tile on the board, zero if it's empty
+    pub fn highest_power(&self) -> usize {
+        self.cells.iter().copied().max().unwrap_or_default()
+    }
+
+    pub fn empty_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, power)| **power == 0)
+            .map(|(idx, _)| idx)
+    }
+
+    /// Slides and merges every tile towards `direction`
+    ///
+    /// No tile is spawned, this is left to the caller through [`GameState::spawn`].
+    pub fn apply(&mut self, direction: Direction) -> MoveOutcome {
+        let mut tracker = MoveTracker::new(self.board.clone(), std::mem::take(&mut self.cells));
+        let outcome = tracker.apply(direction);
+        self.cells = tracker.into_cells();
+
+        self.score += outcome.merged.iter().map(|pow| 2u32.pow(*pow)).sum::<u32>();
+        if outcome.changed {
+            self.moves += 1;
+        }
+
+        outcome
+    }
+
+    /// Places the tiles a game starts with
+    pub fn start(&mut self) {
+        for _ in 0..self.rules.starting_tiles {
+            self.spawn();
+        }
+    }
+
+    /// Applies `direction` then spawns tiles if the board changed, which is what a player's move amounts to
+    pub fn play(&mut self, direction: Direction) -> MoveOutcome {
+        let outcome = self.apply(direction);
+        if outcome.changed {
+            for _ in 0..self.rules.tiles_per_move {
+                self.spawn();
+            }
+        }
+        outcome
+    }
+
+    /// Places a random tile on a random empty cell
+    ///
+    /// Returns the (`index`, `power`) of the new tile or `None` if the board is full.
+    pub fn spawn(&mut self) -> Option<(usize, usize)> {
+        let index = self.random_empty_cell()?;
+        let power = self.random_power();
+        self.place(index, power);
+        Some((index, power))
+    }
+
+    /// Puts a tile of `power` at `index`, replacing whatever was there
+    pub fn place(&mut self, index: usize, power: usize) {
+        self.cells[index] = power;
+    }
+
+    pub fn random_empty_cell(&mut self) -> Option<usize> {
+        let empty: Vec<_> = self.empty_cells().collect();
+        if empty.is_empty() {
+            None
+        } else {
+            Some(empty[self.rng.below(empty.len())])
+        }
+    }
+
+    /// Power of a newly spawned tile, drawn according to the spawn rules
+    pub fn random_power(&mut self) -> usize {
+        let values = &self.rules.values;
+        let total = values.iter().map(|(_, weight)| weight).sum();
+        //without any weight we can't draw anything, so we fall back on the first value or a 2, this can't happen with
+        //rules that passed `SpawnRules::validate`
+        if total == 0 {
+            return values.first().map_or(1, |(power, _)| *power);
+        }
+
+        let mut needle = self.rng.below(total);
+        for &(power, weight) in values {
+            if needle < weight {
+                return power;
+            }
+            needle -= weight;
+        }
+        unreachable!("the needle is always below the total weight")
+    }
+
+    /// Directions which would change the board if applied
+    pub fn legal_moves(&self) -> Vec<Direction> {
+        Direction::ALL
+            .into_iter()
+            .filter(|direction| {
+                let mut tracker = MoveTracker::new(self.board.clone(), self.cells.clone());
+                tracker.apply(*direction);
+                tracker.has_changed()
+            })
+            .collect()
+    }
+
+    /// The game is over once the board is full and no move can free a cell
+    pub fn is_over(&self) -> bool {
+        self.empty_cells().next().is_none() && self.legal_moves().is_empty()
+    }
+}
+
+mod tracker {
+    use super::{Board, Direction, MoveOutcome};
+
+    #[derive(Debug)]
+    pub struct MoveTracker {
+        board: Board,
+        /// The current state of the board
+        tiles: Vec<usize>,
+        changed: bool,
+    }
+
+    /// A tile going from one cell to another, both being the same if it didn't move
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TileSlide {
+        pub from: usize,
+        pub to: usize,
+    }
+
+    /// Two tiles of `power` merging into a single tile of `power + 1`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TileMerge {
+        /// The tile that was the closest to the edge the tiles moved towards, which the other one merged into
+        pub kept: usize,
+        /// The tile which merged into the other one and is no more
+        pub absorbed: usize,
+        /// Cell of the merged tile
+        pub into: usize,
+        /// Power of both tiles, before the merge
+        pub power: u32,
+    }
+
+    impl MoveTracker {
+        pub fn new(board: Board, tiles: Vec<usize>) -> Self {
+            Self {
+                board,
+                tiles,
+                changed: false,
+            }
+        }
+
+        /// Indices of the `nth` row or column, starting from the edge tiles move towards
+        fn stack(&self, direction: Direction, nth: usize) -> Option<Vec<usize>> {
+            use Direction::*;
+            //there are as many horizontal stacks as rows and as many vertical ones as columns
+            let stacks = match direction {
+                Left | Right => self.board.rows,
+                Up | Down => self.board.columns,
+            };
+            if nth >= stacks {
+                None
+            } else {
+                let columns = self.board.columns;
+                let stack: Vec<_> = match direction {
+                    Left | Right => (0..columns).map(|col| nth * columns + col).collect(),
+                    Up | Down => (0..self.board.rows)
+                        .map(|row| row * columns + nth)
+                        .collect(),
+                };
+
+                Some(match direction {
+                    Right | Down => stack.into_iter().rev().collect(),
+                    Left | Up => stack,
+                })
+            }
+        }
+
+        /// Slides and merges every tile towards `direction`
+        pub fn apply(&mut self, direction: Direction) -> MoveOutcome {
+            let mut outcome = MoveOutcome::default();
+            let mut nth = 0;
+            while let Some(stack) = self.stack(direction, nth) {
+                self.slide(&stack, &mut outcome);
+                nth += 1;
+            }
+            outcome.changed = self.changed;
+            outcome
+        }
+
+        /// Moves the tiles of `stack` towards its start, merging pairs of equal tiles along the way
+        ///
+        /// A tile resulting from a merge can't merge again during the same move.
+        fn slide(&mut self, stack: &[usize], outcome: &mut MoveOutcome) {
+            //index in `stack` of the next free cell
+            let mut free = 0;
+            //the last tile placed, if it can still be merged into
+            let mut mergeable: Option<usize> = None;
+
+            for &from in stack {
+                let power = std::mem::take(&mut self.tiles[from]);
+                if power == 0 {
+                    continue;
+                }
+                match mergeable {
+                    Some(kept) if self.tiles[stack[free - 1]] == power => {
+                        let into = stack[free - 1];
+                        self.tiles[into] += 1;
+                        outcome.merged.push(power as u32);
+                        outcome.slides.push(TileSlide { from, to: into });
+                        outcome.merges.push(TileMerge {
+                            kept,
+                            absorbed: from,
+                            into,
+                            power: power as u32,
+                        });
+                        self.changed = true;
+                        mergeable = None;
+                    }
+                    _ => {
+                        let to = stack[free];
+                        self.tiles[to] = power;
+                        outcome.slides.push(TileSlide { from, to });
+                        self.changed |= from != to;
+                        mergeable = Some(from);
+                        free += 1;
+                    }
+                }
+            }
+        }
+
+        /// The power of every cell, zero for empty ones
+        pub fn into_cells(self) -> Vec<usize> {
+            self.tiles
+        }
+
+        pub fn has_changed(&self) -> bool {
+            self.changed
+        }
+
+        pub fn start_tracking(&mut self) {
+            self.changed = false;
+        }
+    }
+}
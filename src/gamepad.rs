@@ -0,0 +1,256 @@
+use crate::{engine::Direction, *};
+
+use bevy::{prelude::*, ui::UiSystem, utils::HashMap};
+
+#[derive(Debug, Copy, Clone)]
+pub struct GamepadInputPlugin;
+
+impl Plugin for GamepadInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Flick>()
+            .init_resource::<MenuFocus>()
+            .add_systems(
+                PreUpdate,
+                activate_focused_button
+                    .after(UiSystem::Focus)
+                    .run_if(not(in_state(AppState::InGame))),
+            )
+            .add_systems(
+                Update,
+                (
+                    read_flicks,
+                    control_playback_with_gamepad
+                        .before(MoveInput)
+                        .run_if(in_state(AppState::InGame))
+                        .run_if(resource_exists::<Playback>()),
+                    move_with_gamepad
+                        .in_set(MoveInput)
+                        .run_if(in_state(AppState::InGame))
+                        .run_if(not_replaying),
+                    (navigate_menus, highlight_focused_button)
+                        .chain()
+                        .run_if(not(in_state(AppState::InGame))),
+                    pause_with_gamepad,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// A direction pushed on a gamepad, either with its D-pad or by flicking its left stick
+#[derive(Debug, Clone, Copy, Event)]
+struct Flick(Direction);
+
+/// Button of the current menu selected with a gamepad
+#[derive(Debug, Resource, Default)]
+pub struct MenuFocus(pub Option<Entity>);
+
+const DPAD: [(GamepadButtonType, Direction); 4] = [
+    (GamepadButtonType::DPadLeft, Direction::Left),
+    (GamepadButtonType::DPadUp, Direction::Up),
+    (GamepadButtonType::DPadRight, Direction::Right),
+    (GamepadButtonType::DPadDown, Direction::Down),
+];
+
+/// Turns D-pad presses and stick flicks into [`Flick`]s
+///
+/// A flick happens when the stick leaves the dead zone set in the [`Preferences`], the stick must then go back
+/// close to the center before it can flick again.
+fn read_flicks(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    preferences: Res<Preferences>,
+    mut flicks: EventWriter<Flick>,
+    //whether each gamepad's stick is out of the dead zone
+    mut flicked: Local<HashMap<Gamepad, bool>>,
+) {
+    for gamepad in gamepads.iter() {
+        for (button, direction) in DPAD {
+            if buttons.just_pressed(GamepadButton::new(gamepad, button)) {
+                flicks.send(Flick(direction));
+            }
+        }
+
+        let axis = |axis| {
+            axes.get(GamepadAxis::new(gamepad, axis))
+                .unwrap_or_default()
+        };
+        let stick = Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        let flicked = flicked.entry(gamepad).or_default();
+        if *flicked {
+            *flicked = stick.length() > preferences.stick_dead_zone / 2.0;
+        } else if stick.length() > preferences.stick_dead_zone {
+            *flicked = true;
+            //the stick's y axis points up
+            flicks.send(Flick(if stick.x.abs() > stick.y.abs() {
+                if stick.x < 0.0 {
+                    Direction::Left
+                } else {
+                    Direction::Right
+                }
+            } else if stick.y > 0.0 {
+                Direction::Up
+            } else {
+                Direction::Down
+            }));
+        }
+    }
+}
+
+fn move_with_gamepad(mut flicks: EventReader<Flick>, mut directions: EventWriter<Direction>) {
+    //only one move is applied per frame, just like with the keyboard
+    if let Some(Flick(direction)) = flicks.read().next() {
+        directions.send(*direction)
+    }
+}
+
+/// Sends [`PlaybackControl`]s while watching a replay
+///
+/// Flicks act like the move keys, south pauses and the left and right bumpers go to the start and end.
+fn control_playback_with_gamepad(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut flicks: EventReader<Flick>,
+    mut controls: EventWriter<PlaybackControl>,
+) {
+    for Flick(direction) in flicks.read() {
+        controls.send(match direction {
+            Direction::Left => PlaybackControl::StepBack,
+            Direction::Up => PlaybackControl::Faster,
+            Direction::Right => PlaybackControl::StepForward,
+            Direction::Down => PlaybackControl::Slower,
+        });
+    }
+    for gamepad in gamepads.iter() {
+        for (button, control) in [
+            (GamepadButtonType::South, PlaybackControl::TogglePause),
+            (GamepadButtonType::LeftTrigger, PlaybackControl::ToStart),
+            (GamepadButtonType::RightTrigger, PlaybackControl::ToEnd),
+        ] {
+            if buttons.just_pressed(GamepadButton::new(gamepad, button)) {
+                controls.send(control);
+            }
+        }
+    }
+}
+
+/// Moves the focus to the closest button in the direction flicked
+///
+/// The first flick focuses the top left button of the menu.
+fn navigate_menus(
+    mut flicks: EventReader<Flick>,
+    mut focus: ResMut<MenuFocus>,
+    buttons: Query<(Entity, &GlobalTransform, &ViewVisibility), With<Button>>,
+) {
+    for Flick(direction) in flicks.read() {
+        //ui coordinates grow rightwards and downwards
+        let towards = match direction {
+            Direction::Left => Vec2::NEG_X,
+            Direction::Up => Vec2::NEG_Y,
+            Direction::Right => Vec2::X,
+            Direction::Down => Vec2::Y,
+        };
+        let visible = buttons
+            .iter()
+            .filter(|(_, _, visibility)| visibility.get())
+            .map(|(entity, transform, _)| (entity, transform.translation().truncate()));
+
+        let current = focus.0.and_then(|entity| buttons.get(entity).ok());
+        focus.0 = match current {
+            Some((focused, transform, _)) => {
+                let from = transform.translation().truncate();
+                visible
+                    .filter(|(entity, _)| *entity != focused)
+                    .filter_map(|(entity, position)| {
+                        let offset = position - from;
+                        let along = offset.dot(towards);
+                        //buttons out of line are further away than those straight in the direction
+                        let across = offset.perp_dot(towards).abs();
+                        (along > 0.0).then_some((entity, along + 2.0 * across))
+                    })
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(entity, _)| entity)
+                    .or(Some(focused))
+            }
+            None => visible
+                .min_by(|(_, a), (_, b)| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap())
+                .map(|(entity, _)| entity),
+        };
+    }
+}
+
+#[derive(Debug, Component)]
+struct Highlighted;
+
+fn highlight_focused_button(
+    mut commands: Commands,
+    focus: Res<MenuFocus>,
+    mut buttons: Query<(Entity, &mut Style, &mut BorderColor, Has<Highlighted>), With<Button>>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    for (entity, mut style, mut border, highlighted) in &mut buttons {
+        let focused = focus.0 == Some(entity);
+        if focused == highlighted {
+            continue;
+        }
+        if focused {
+            style.border = UiRect::all(Val::Px(4.0));
+            border.0 = Color::BLACK;
+            commands.entity(entity).insert(Highlighted);
+        } else {
+            style.border = UiRect::default();
+            commands.entity(entity).remove::<Highlighted>();
+        }
+    }
+}
+
+/// Presses the focused button when south (A on Xbox controllers) is pressed
+///
+/// This runs right after the mouse interactions are computed, so that the buttons react the same way as when they
+/// are clicked.
+fn activate_focused_button(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    focus: Res<MenuFocus>,
+    mut interactions: Query<&mut Interaction>,
+    //button pressed by the gamepad in the last frame, released now that it was handled
+    mut pressed: Local<Option<Entity>>,
+) {
+    if let Some(mut interaction) = pressed
+        .take()
+        .and_then(|entity| interactions.get_mut(entity).ok())
+    {
+        if *interaction == Interaction::Pressed {
+            *interaction = Interaction::None;
+        }
+    }
+
+    let activated = gamepads
+        .iter()
+        .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South)));
+    let Some(focused) = focus.0.filter(|_| activated) else {
+        return;
+    };
+    if let Ok(mut interaction) = interactions.get_mut(focused) {
+        *interaction = Interaction::Pressed;
+        *pressed = Some(focused);
+    }
+}
+
+fn pause_with_gamepad(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut toggle_pause: EventWriter<TogglePause>,
+) {
+    for gamepad in gamepads.iter() {
+        if buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start)) {
+            toggle_pause.send(TogglePause);
+        }
+    }
+}
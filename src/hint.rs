@@ -0,0 +1,200 @@
+use crate::{engine::Direction, *};
+
+use bevy::prelude::*;
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone)]
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RequestHint>()
+            .add_systems(OnExit(AppState::InGame), clear_hint)
+            .add_systems(
+                Update,
+                (
+                    hint_with_keybind,
+                    hint_with_button,
+                    clear_hint_after_move.after(apply_move),
+                    show_hint,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not_replaying),
+            );
+    }
+}
+
+/// Asks for the best move from the current board
+#[derive(Debug, Clone, Copy, Event)]
+pub struct RequestHint;
+
+/// Asks for a hint when clicked
+#[derive(Debug, Component)]
+pub struct HintButton;
+
+/// Arrow and values shown by a hint, until the next move
+#[derive(Debug, Component)]
+struct HintOverlay;
+
+/// Search giving the hints, which can think longer than the [`AiPlayer`] since the player waits for it only once
+const HINT_SEARCH: Expectimax = Expectimax {
+    depth: 5,
+    time_budget: Duration::from_millis(200),
+};
+
+fn arrow(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Left => "←",
+        Direction::Up => "↑",
+        Direction::Right => "→",
+        Direction::Down => "↓",
+    }
+}
+
+fn hint_with_keybind(
+    keys: Res<Input<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut requests: EventWriter<RequestHint>,
+) {
+    if keybinds.just_pressed(Action::Hint, &keys) {
+        requests.send(RequestHint)
+    }
+}
+
+fn hint_with_button(
+    query: Query<&Interaction, (Changed<Interaction>, With<HintButton>)>,
+    mut requests: EventWriter<RequestHint>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        requests.send(RequestHint)
+    }
+}
+
+fn clear_hint(mut commands: Commands, overlay: Query<Entity, With<HintOverlay>>) {
+    for entity in &overlay {
+        commands.entity(entity).despawn_recursive()
+    }
+}
+
+fn clear_hint_after_move(
+    commands: Commands,
+    mut moves: EventReader<MoveApplied>,
+    overlay: Query<Entity, With<HintOverlay>>,
+) {
+    if moves.read().any(|applied| applied.changed) {
+        clear_hint(commands, overlay)
+    }
+}
+
+/// Points at the best move over the board, and lists the expected value of every move in the footer
+///
+/// The search runs in the background, its outcome is dropped if a move was made in the meantime.
+fn show_hint(
+    mut commands: Commands,
+    mut requests: EventReader<RequestHint>,
+    mut pending: Local<Option<PendingSearch<Vec<(Direction, f32)>>>>,
+    mut history: ResMut<History>,
+    game: Res<GameState>,
+    tiling: Res<Tiling>,
+    board: Res<Board>,
+    asset_server: Res<AssetServer>,
+    overlay: Query<Entity, With<HintOverlay>>,
+) {
+    if requests.read().last().is_some() && pending.is_none() {
+        *pending = Some(PendingSearch::spawn(&game, |game| {
+            HINT_SEARCH.evaluate(game)
+        }));
+    }
+    let Some(search) = pending.as_mut() else {
+        return;
+    };
+    let Some(values) = search.poll() else {
+        return;
+    };
+    let current = search.started_from(&game);
+    *pending = None;
+    if !current {
+        return;
+    }
+    let Some((best, _)) = values
+        .iter()
+        .copied()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    else {
+        return;
+    };
+    history.record_hint();
+    for entity in &overlay {
+        commands.entity(entity).despawn_recursive()
+    }
+
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let highlight = Color::hex("f65e3b").unwrap();
+    //the center of the board is halfway between its first and last cells
+    let center = (Position::from(0).to_translation(&tiling, &board)
+        + Position::from(board.size() - 1).to_translation(&tiling, &board))
+        / 2.0;
+    commands.spawn((
+        HintOverlay,
+        Text2dBundle {
+            text: Text::from_section(
+                arrow(best),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: tiling.tile_size().y * 2.0,
+                    color: highlight.with_a(0.8),
+                },
+            ),
+            //above the tiles and the score popups
+            transform: Transform::from_translation(center.truncate().extend(3.0)),
+            ..default()
+        },
+    ));
+
+    let sections = Direction::ALL.map(|direction| {
+        let value = values
+            .iter()
+            .find(|(legal, _)| *legal == direction)
+            .map_or("-".to_string(), |(_, value)| format!("{value:.0}"));
+        TextSection::new(
+            format!(" {} {value} ", arrow(direction)),
+            TextStyle {
+                font: font.clone(),
+                font_size: 32.0,
+                color: if direction == best {
+                    highlight
+                } else {
+                    Color::WHITE
+                },
+            },
+        )
+    });
+    commands
+        .spawn((
+            HintOverlay,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Px(FOOTER_HEIGHT),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_sections(sections),
+                style: Style {
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.47, 0.43, 0.4, 0.9).into(),
+                ..default()
+            });
+        });
+}
@@ -0,0 +1,251 @@
+use crate::{engine::Direction, *};
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Debug, Copy, Clone)]
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Undo>()
+            .add_event::<Redo>()
+            .init_resource::<History>()
+            .init_resource::<UndoRules>()
+            .add_systems(OnEnter(AppState::Setup), reset_history)
+            .add_systems(
+                Update,
+                (
+                    undo_with_keybind.before(travel_history),
+                    (travel_history, record_history)
+                        .chain()
+                        .after(SelectDirection)
+                        .before(apply_move),
+                )
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not_replaying),
+            );
+    }
+}
+
+/// Takes back the last move
+#[derive(Debug, Event, Clone, Copy)]
+pub struct Undo;
+
+/// Plays again the last move which was taken back
+#[derive(Debug, Event, Clone, Copy)]
+pub struct Redo;
+
+/// Everything needed to put the game back as it was before a move
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub game: GameState,
+    pub score: Score,
+    pub congratulation: Congratulation,
+}
+
+#[derive(Debug, Resource)]
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// Maximum number of moves that can be taken back
+    pub capacity: usize,
+    undos_used: u32,
+    /// Number of moves the AI made during this game
+    ai_moves: u32,
+    /// Number of hints asked for during this game
+    hints_used: u32,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            capacity: 128,
+            undos_used: 0,
+            ai_moves: 0,
+            hints_used: 0,
+        }
+    }
+}
+
+impl History {
+    /// Saves the state of the board before a move, which forgets about any move that was undone
+    ///
+    /// Nothing is kept with a capacity of 0.
+    pub fn record(&mut self, snapshot: Snapshot) {
+        self.redo.clear();
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo.len() >= self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+    }
+
+    /// Swaps `current` with the state before the last move, if any
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        self.undos_used += 1;
+        Some(previous)
+    }
+
+    /// Swaps `current` with the state after the last undone move, if any
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+
+    /// Number of moves taken back during this game
+    pub fn undos_used(&self) -> u32 {
+        self.undos_used
+    }
+
+    pub fn record_ai_move(&mut self) {
+        self.ai_moves += 1;
+    }
+
+    /// Number of moves the AI made during this game
+    pub fn ai_moves(&self) -> u32 {
+        self.ai_moves
+    }
+
+    pub fn record_hint(&mut self) {
+        self.hints_used += 1;
+    }
+
+    /// Number of hints asked for during this game, they don't make it assisted
+    pub fn hints_used(&self) -> u32 {
+        self.hints_used
+    }
+
+    /// A game is assisted as soon as a single move was taken back or made by the AI, its score shouldn't be compared
+    /// with others
+    pub fn is_assisted(&self) -> bool {
+        self.undos_used > 0 || self.ai_moves > 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = History {
+            capacity: self.capacity,
+            ..default()
+        }
+    }
+}
+
+/// Restrictions on undoing moves
+#[derive(Debug, Resource, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UndoRules {
+    /// Maximum number of undos per game, `None` meaning unlimited
+    pub limit: Option<u32>,
+}
+
+impl UndoRules {
+    pub fn allows(&self, history: &History) -> bool {
+        self.limit.is_none_or(|limit| history.undos_used < limit)
+    }
+}
+
+/// Forgets the previous game, a resumed one keeps counting how it was assisted so that it stays so
+fn reset_history(
+    mut history: ResMut<History>,
+    mut rules: ResMut<UndoRules>,
+    resume: Option<Res<ResumeGame>>,
+) {
+    history.clear();
+    if let Some(ResumeGame(saved)) = resume.as_deref() {
+        history.undos_used = saved.undos_used;
+        history.ai_moves = saved.ai_moves;
+        history.hints_used = saved.hints_used;
+        //the game goes on with the undo limit it was started with
+        if let Some(replay) = &saved.replay {
+            rules.limit = replay.undo_limit;
+        }
+    }
+}
+
+fn undo_with_keybind(
+    keys: Res<Input<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut undo: EventWriter<Undo>,
+    mut redo: EventWriter<Redo>,
+) {
+    if keybinds.just_pressed(Action::Undo, &keys) {
+        undo.send(Undo)
+    } else if keybinds.just_pressed(Action::Redo, &keys) {
+        redo.send(Redo)
+    }
+}
+
+/// Saves the board before a move that will change it is applied
+fn record_history(
+    mut directions: EventReader<Direction>,
+    mut history: ResMut<History>,
+    game: Res<GameState>,
+    score: Res<Score>,
+    congratulation: Res<Congratulation>,
+) {
+    //only the first direction is applied by `apply_move`
+    let Some(direction) = directions.read().next() else {
+        return;
+    };
+    if game.legal_moves().contains(direction) {
+        history.record(Snapshot {
+            game: game.clone(),
+            score: score.clone(),
+            congratulation: *congratulation,
+        })
+    }
+}
+
+fn travel_history(
+    mut commands: Commands,
+    mut undo: EventReader<Undo>,
+    mut redo: EventReader<Redo>,
+    mut history: ResMut<History>,
+    mut game: ResMut<GameState>,
+    mut score: ResMut<Score>,
+    mut directions: ResMut<Events<Direction>>,
+    mut queue: ResMut<MoveQueue>,
+    mut new_tiles: EventWriter<SpawnTile>,
+    mut congratulation: ResMut<Congratulation>,
+    rules: Res<UndoRules>,
+    tiles: Query<Entity, With<Tile>>,
+) {
+    let current = Snapshot {
+        game: game.clone(),
+        score: score.clone(),
+        congratulation: *congratulation,
+    };
+    //undo and redo cancel each other out, when both are asked for in the same frame undoing wins
+    let snapshot = match (undo.read().last(), redo.read().last()) {
+        (None, None) => return,
+        (Some(_), _) if !rules.allows(&history) => {
+            info!("No undo left for this game");
+            return;
+        }
+        (Some(_), _) => history.undo(current),
+        (None, Some(_)) => history.redo(current),
+    };
+    let Some(snapshot) = snapshot else {
+        return;
+    };
+
+    //a move selected in the same frame, or made before and still waiting, would be applied to the wrong board
+    directions.clear();
+    queue.0.clear();
+
+    *game = snapshot.game;
+    *score = snapshot.score;
+    *congratulation = snapshot.congratulation;
+
+    //rebuilding the entities mirroring the board
+    for entity in &tiles {
+        commands.entity(entity).despawn_recursive();
+    }
+    new_tiles.send_batch(SpawnTile::mirror(&game));
+}
@@ -0,0 +1,225 @@
+use crate::*;
+
+use bevy::prelude::*;
+
+#[derive(Debug, Copy, Clone)]
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), spawn_hud)
+            .add_systems(OnExit(AppState::InGame), despawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_hud,
+                    spawn_score_popups.after(apply_move),
+                    float_score_popups,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// Height in pixels of the bar at the top of the window, the board is laid out below it
+pub const HUD_HEIGHT: f32 = 80.0;
+
+/// Height in pixels of the strip kept clear at the bottom of the window, where hints show the value of each move
+pub const FOOTER_HEIGHT: f32 = 64.0;
+
+/// Seconds a popup takes to float away
+const POPUP_DURATION: f32 = 0.6;
+
+#[derive(Debug, Component)]
+struct Hud;
+
+/// Value shown by a counter of the HUD
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq)]
+enum Counter {
+    Score,
+    BestScore,
+    Moves,
+}
+
+impl Counter {
+    const ALL: [Counter; 3] = [Counter::Score, Counter::BestScore, Counter::Moves];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Counter::Score => "Score",
+            Counter::BestScore => "Best",
+            Counter::Moves => "Moves",
+        }
+    }
+}
+
+/// Points won by a merge, drifting up from where it happened
+#[derive(Debug, Component)]
+struct ScorePopup {
+    /// Seconds since the merge
+    elapsed: f32,
+}
+
+fn spawn_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    preferences: Res<Preferences>,
+    playback: Option<Res<Playback>>,
+) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    commands
+        .spawn((
+            Hud,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Px(HUD_HEIGHT),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(48.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for counter in Counter::ALL {
+                parent.spawn((
+                    counter,
+                    TextBundle {
+                        text: Text {
+                            sections: vec![
+                                TextSection::new(
+                                    format!("{}\n", counter.name()),
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 24.0,
+                                        color: Color::hex("bbada0").unwrap(),
+                                    },
+                                ),
+                                TextSection::new(
+                                    "0",
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 36.0,
+                                        color: preferences.theme.text(),
+                                    },
+                                ),
+                            ],
+                            alignment: TextAlignment::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+
+            //hints are pointless when watching a replay
+            if playback.is_none() {
+                parent
+                    .spawn((
+                        HintButton,
+                        ButtonBundle {
+                            background_color: Color::hex("8f7a66").unwrap().into(),
+                            style: Style {
+                                width: Val::Px(120.0),
+                                height: Val::Px(48.0),
+                                ..DEFAULT_BUTTON_STYLE
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Hint",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 28.0,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+fn despawn_hud(mut commands: Commands, hud: Query<Entity, Or<(With<Hud>, With<ScorePopup>)>>) {
+    for entity in &hud {
+        commands.entity(entity).despawn_recursive()
+    }
+}
+
+fn update_hud(
+    score: Res<Score>,
+    game: Res<GameState>,
+    best_scores: Res<BestScores>,
+    mut counters: Query<(Ref<Counter>, &mut Text)>,
+) {
+    let changed = score.is_changed() || game.is_changed() || best_scores.is_changed();
+    for (counter, mut text) in &mut counters {
+        if !changed && !counter.is_added() {
+            continue;
+        }
+        let value = match *counter {
+            Counter::Score => score.0,
+            //the best score is only recorded once the game is over, but the player may already be beating it
+            Counter::BestScore => best_scores.get(game.board()).max(score.0),
+            Counter::Moves => game.moves(),
+        };
+        text.sections[1].value = value.to_string();
+    }
+}
+
+fn spawn_score_popups(
+    mut commands: Commands,
+    mut merges: EventReader<TilesMerged>,
+    asset_server: Res<AssetServer>,
+    tiling: Res<Tiling>,
+    board: Res<Board>,
+) {
+    for merge in merges.read() {
+        let mut translation = merge.into.to_translation(&tiling, &board);
+        //above the tiles, even those popping
+        translation.z = 2.0;
+        commands.spawn((
+            ScorePopup { elapsed: 0.0 },
+            Text2dBundle {
+                //a merge scores the value of the tiles merged, as in `score_from_merge`
+                text: Text::from_section(
+                    format!("+{}", merge.value / 2),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                        font_size: 40.0,
+                        color: Color::hex("776e65").unwrap(),
+                    },
+                ),
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn float_score_popups(
+    mut commands: Commands,
+    mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+    tiling: Res<Tiling>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut transform, mut text) in &mut popups {
+        popup.elapsed += time.delta_seconds();
+        if popup.elapsed >= POPUP_DURATION {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        //rising by half a tile while fading out
+        let step = time.delta_seconds() / POPUP_DURATION;
+        transform.translation.y += step * tiling.tile_size().y / 2.0;
+        let alpha = 1.0 - popup.elapsed / POPUP_DURATION;
+        for section in &mut text.sections {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
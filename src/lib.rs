@@ -1,19 +1,47 @@
 #![allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub mod achievements;
+pub mod ai;
+pub mod animation;
 pub mod assets;
 pub mod audio;
+pub mod engine;
+pub mod gamepad;
+pub mod hint;
+pub mod history;
+pub mod hud;
 pub mod moving;
+pub mod replay;
+pub mod save;
+pub mod scores;
 pub mod settings;
+pub mod stats;
+pub mod storage;
+pub mod swipe;
 pub mod tiling;
 pub mod ui;
 
+pub use achievements::*;
+pub use ai::*;
+pub use animation::*;
 pub use assets::*;
 pub use audio::*;
+pub use engine::*;
+pub use gamepad::*;
+pub use hint::*;
+pub use history::*;
+pub use hud::*;
 pub use moving::*;
+pub use replay::*;
+pub use save::*;
+pub use scores::*;
 pub use settings::*;
+pub use stats::*;
+pub use swipe::*;
 pub use tiling::*;
 pub use ui::*;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum AppState {
@@ -22,37 +50,119 @@ pub enum AppState {
     Setup,
     InGame,
     MainMenu,
+    NewGameMenu,
     SettingsMenu,
     Paused,
     WonMenu,
     LostMenu,
     CongratsMenu,
+    LeaderboardMenu,
+    StatsMenu,
+    AchievementsMenu,
+    ReplaysMenu,
 }
 
 #[derive(Event, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FinishGame {
     GameOver,
+    /// The player went back to the main menu, the game can be continued from there
     Quit,
+    /// The player gave up to start another game right away
+    Restart,
+}
+
+impl FinishGame {
+    /// Whether the game can't be continued anymore, only then does it count in the scores and statistics
+    ///
+    /// Games that were restarted are over too: their score is kept even though the player gave up.
+    pub fn is_over(&self) -> bool {
+        *self != FinishGame::Quit
+    }
+}
+
+/// A new game was set up, games resumed or rebuilt after undoing moves don't count
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct GameStarted {
+    pub seed: u64,
+    pub rules: SpawnRules,
+}
+
+/// A tile reached one of the [`WinTarget`]'s milestones for the first time during the game
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MilestoneReached {
+    /// Power of the milestone, 11 being 2048
+    pub power: u32,
 }
 
 #[derive(Debug, Resource, Clone, PartialEq, Eq, Default)]
 pub struct Score(pub u32);
 
-impl Score {
-    pub fn has_won(&self) -> bool {
-        self.0 > 0
+/// Tile to reach to win a game
+#[derive(Debug, Resource, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WinTarget {
+    /// Power of the winning tile, 11 being 2048
+    pub power: u32,
+    /// Whether every doubling of the winning tile (4096, 8192...) is congratulated as well
+    pub every_milestone: bool,
+}
+
+impl Default for WinTarget {
+    fn default() -> Self {
+        WinTarget {
+            power: 11,
+            every_milestone: true,
+        }
+    }
+}
+
+impl WinTarget {
+    /// Whether a game whose highest tile is of `power` is won
+    pub fn is_reached(&self, power: usize) -> bool {
+        power as u32 >= self.power
+    }
+
+    /// Highest milestone reached by a tile of `power`, if any
+    pub fn milestone(&self, power: usize) -> Option<u32> {
+        match power as u32 {
+            power if power < self.power => None,
+            power if self.every_milestone => Some(power),
+            _ => Some(self.power),
+        }
     }
 }
 
+/// Seed of the next game, a random one is picked when `None`
+///
+/// It can be set from the command line with `--seed <seed>` or from the main menu.
+#[derive(Debug, Resource, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GameSeed(pub Option<u64>);
+
 pub fn score_from_merge(mut score: ResMut<Score>, mut merges: EventReader<Merged>) {
     for merge in merges.read() {
         score.0 += merge.power()
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-pub enum Congratulation {
-    Congratulated,
-    #[default]
-    NotYet,
+/// Sends a [`MilestoneReached`] when the highest tile reaches a milestone for the first time
+pub fn detect_milestones(
+    game: Res<GameState>,
+    target: Res<WinTarget>,
+    mut congratulation: ResMut<Congratulation>,
+    mut milestones: EventWriter<MilestoneReached>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+    //the highest tile tells which milestones were reached, we only send them once for each of them
+    match target.milestone(game.highest_power()) {
+        Some(power) if power > congratulation.0 => {
+            congratulation.0 = power;
+            milestones.send(MilestoneReached { power })
+        }
+        _ => (),
+    }
 }
+
+/// Power of the last milestone the player was congratulated for during the current game, zero if none
+#[derive(Debug, Resource, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Congratulation(pub u32);
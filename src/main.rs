@@ -23,41 +23,94 @@ fn main() {
         }))
         .add_state::<AppState>()
         .add_event::<FinishGame>()
+        .add_event::<GameStarted>()
+        .add_event::<MilestoneReached>()
         .insert_resource(ClearColor(Color::WHITE))
         .init_resource::<Score>()
+        .init_resource::<WinTarget>()
+        .init_resource::<Congratulation>()
+        .insert_resource(GameSeed(seed_from_args()))
+        .insert_resource(ReplayPath(arg_value("--replay")))
         .add_plugins((
             GameAssetsPlugin,
             UserSettingsPlugin,
             TilingPlugin,
             MovingPlugin,
+            TileAnimationPlugin,
+            GamepadInputPlugin,
+            SwipePlugin,
+            //the AI playing in the player's stead or hinting at the best move
+            (AiPlugin, HintPlugin),
+            HistoryPlugin,
+            HudPlugin,
+            SavePlugin,
+            ReplayPlugin,
+            //what is kept of the games once they're over
+            (ScoresPlugin, StatsPlugin, AchievementsPlugin),
             MusicPlugin,
             GameInterfacePlugin,
         ))
-        .add_systems(OnEnter(AppState::Setup), (game_setup, reset_score))
+        .add_systems(
+            OnEnter(AppState::Setup),
+            (reset_score, game_setup, start_recording).chain(),
+        )
         .add_systems(OnExit(AppState::Loading), app_setup)
         //the systems responsible for running the game
         .add_systems(
             Update,
             (
                 ((game_over.after(spawn_tile),),).after(game_setup),
-                detect_stale_board.after(apply_move),
+                detect_stale_board
+                    .after(apply_move)
+                    .run_if(in_state(AppState::InGame)),
                 score_from_merge,
+                detect_milestones
+                    .after(apply_move)
+                    .run_if(in_state(AppState::InGame)),
+                restart_with_keybind
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not_replaying),
             ),
         )
         .run();
 }
 
+/// Reads the value following `name` on the command line, if any
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    args.find(|arg| arg == name)?;
+    args.next()
+}
+
+/// Reads the seed passed with `--seed <seed>`, if any
+fn seed_from_args() -> Option<u64> {
+    let seed = arg_value("--seed")?;
+    let Ok(seed) = seed.parse() else {
+        warn!("`--seed` expects a positive integer, a random seed will be used");
+        return None;
+    };
+    Some(seed)
+}
+
 fn app_setup(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
 
 fn game_setup(
+    mut commands: Commands,
     mut spawn_tiles: EventWriter<SpawnTile>,
+    mut game_started: EventWriter<GameStarted>,
     mut app_state: ResMut<NextState<AppState>>,
-    mut congrats_state: ResMut<NextState<Congratulation>>,
+    mut congratulation: ResMut<Congratulation>,
     mut tiling: ResMut<Tiling>,
+    mut game: ResMut<GameState>,
+    mut score: ResMut<Score>,
+    mut board: ResMut<Board>,
+    seed: Res<GameSeed>,
+    rules: Res<SpawnRules>,
+    resume: Option<Res<ResumeGame>>,
+    playback: Option<Res<Playback>>,
     handles: Res<TileHandles>,
-    board: Res<Board>,
     assets: Res<Assets<Image>>,
     window: Query<&Window, With<PrimaryWindow>>,
 ) {
@@ -66,6 +119,11 @@ fn game_setup(
         error!("no primary window");
         return;
     };
+    if let Some(ResumeGame(saved)) = resume.as_deref() {
+        *board = saved.game.board().clone();
+    } else if let Some(playback) = &playback {
+        *board = playback.replay().board.clone();
+    }
     resize_tiling(
         &mut tiling,
         &board,
@@ -74,11 +132,41 @@ fn game_setup(
         primary.height(),
     );
 
-    //placing initial tiles
-    spawn_tiles.send_batch([SpawnTile::default(); 2]);
+    if let Some(ResumeGame(saved)) = resume.as_deref() {
+        //picking up where the player left
+        *game = saved.game.clone();
+        score.0 = saved.score;
+        spawn_tiles.send_batch(SpawnTile::mirror(&game));
+        *congratulation = saved.congratulation;
+        commands.remove_resource::<ResumeGame>();
+        info!("Resuming game with seed {}", game.seed());
+    } else {
+        //starting from an empty board
+        //a replay is played from its own seed and rules
+        let (seed, rules) = match &playback {
+            Some(playback) => (
+                Some(playback.replay().seed),
+                playback.replay().spawn_rules.clone(),
+            ),
+            None => (seed.0, rules.clone()),
+        };
+        *game = match seed {
+            Some(seed) => GameState::with_seed(board.clone(), seed),
+            None => GameState::new(board.clone()),
+        }
+        .with_rules(rules);
+        info!("Starting a new game with seed {}", game.seed());
+        game_started.send(GameStarted {
+            seed: game.seed(),
+            rules: game.rules().clone(),
+        });
+
+        //placing initial tiles
+        spawn_tiles.send_batch(vec![SpawnTile::default(); game.rules().starting_tiles]);
 
-    //we haven't congratulated yet since no 2048 was created
-    congrats_state.set(Congratulation::NotYet);
+        //we haven't congratulated yet since no milestone was reached
+        *congratulation = Congratulation::default();
+    }
 
     //starting the game
     app_state.set(AppState::InGame);
@@ -91,7 +179,8 @@ fn game_over(
     mut game_over: EventReader<FinishGame>,
     mut app_state: ResMut<NextState<AppState>>,
     tiles_query: Query<Entity, With<Tile>>,
-    score: Res<Score>,
+    game: Res<GameState>,
+    target: Res<WinTarget>,
 ) {
     game_over.read().enumerate().for_each(|(i, reason)| {
         if i != 0 {
@@ -103,8 +192,10 @@ fn game_over(
 
         app_state.set(match reason {
             FinishGame::Quit => AppState::MainMenu,
+            FinishGame::Restart => AppState::Setup,
             FinishGame::GameOver => {
-                if score.has_won() {
+                //the game is won if the winning tile was made, even if it was merged since
+                if target.is_reached(game.highest_power()) {
                     AppState::WonMenu
                 } else {
                     AppState::LostMenu
@@ -114,6 +205,16 @@ fn game_over(
     });
 }
 
+fn restart_with_keybind(
+    keys: Res<Input<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut game_over: EventWriter<FinishGame>,
+) {
+    if keybinds.just_pressed(Action::Restart, &keys) {
+        game_over.send(FinishGame::Restart)
+    }
+}
+
 fn reset_score(mut score: ResMut<Score>) {
     score.0 = 0
 }
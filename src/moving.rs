@@ -1,6 +1,7 @@
-use crate::{settings::Keybinds, *};
+use crate::{engine::Direction, *};
 
-use bevy::prelude::*;
+use bevy::{ecs::event::ManualEventReader, prelude::*, utils::HashMap};
+use std::collections::VecDeque;
 
 #[derive(Debug, Copy, Clone)]
 pub struct MovingPlugin;
@@ -8,24 +9,68 @@ pub struct MovingPlugin;
 impl Plugin for MovingPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<Merged>()
+            .add_event::<MoveApplied>()
+            .add_event::<TileMoved>()
+            .add_event::<TilesMerged>()
             .add_event::<Direction>()
+            .init_resource::<MoveQueue>()
+            .configure_sets(Update, MoveInput.before(SelectDirection))
+            .add_systems(OnEnter(AppState::Setup), clear_move_queue)
             .add_systems(
                 Update,
                 (
-                    select_direction,
-                    apply_move.after(select_direction).before(spawn_tile),
+                    select_direction.in_set(MoveInput).run_if(not_replaying),
+                    queue_moves.in_set(SelectDirection),
+                    apply_move.after(SelectDirection).before(spawn_tile),
                 )
                     .run_if(in_state(AppState::InGame)),
             );
     }
 }
 
-#[derive(Debug, Event, Copy, Clone, PartialEq, Eq)]
-pub enum Direction {
-    Left,
-    Up,
-    Right,
-    Down,
+impl Event for Direction {}
+
+/// Systems sending [`Direction`] events
+///
+/// Everything that needs to look at the board before a move is applied should run after this set and before
+/// [`apply_move`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct SelectDirection;
+
+/// Systems turning the player's input into [`Direction`] events, which are then queued in the [`MoveQueue`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct MoveInput;
+
+/// Moves made while the tiles were still sliding, applied one after the other once they stop
+#[derive(Debug, Resource, Default)]
+pub struct MoveQueue(pub VecDeque<Direction>);
+
+/// Lets at most one [`Direction`] event through per frame, and none while tiles are sliding
+fn queue_moves(
+    mut directions: ResMut<Events<Direction>>,
+    mut queue: ResMut<MoveQueue>,
+    animations: Query<&TileAnimation>,
+    preferences: Res<Preferences>,
+    //our own reader, so that the moves we let through aren't queued once more
+    mut reader: Local<ManualEventReader<Direction>>,
+) {
+    queue.0.extend(reader.read(&directions).copied());
+    directions.clear();
+
+    let sliding = animations
+        .iter()
+        .any(|animation| animation.is_sliding(preferences.animation_duration));
+    if sliding {
+        return;
+    }
+    if let Some(direction) = queue.0.pop_front() {
+        directions.send(direction);
+        reader.read(&directions).for_each(drop);
+    }
+}
+
+fn clear_move_queue(mut queue: ResMut<MoveQueue>) {
+    queue.0.clear()
 }
 
 fn select_direction(
@@ -33,13 +78,13 @@ fn select_direction(
     mut next_direction: EventWriter<Direction>,
     keybinds: Res<Keybinds>,
 ) {
-    let direction = if keys.just_released(keybinds.move_left) {
+    let direction = if keybinds.just_released(Action::MoveLeft, &keys) {
         Direction::Left
-    } else if keys.just_released(keybinds.move_up) {
+    } else if keybinds.just_released(Action::MoveUp, &keys) {
         Direction::Up
-    } else if keys.just_released(keybinds.move_right) {
+    } else if keybinds.just_released(Action::MoveRight, &keys) {
         Direction::Right
-    } else if keys.just_released(keybinds.move_down) {
+    } else if keybinds.just_released(Action::MoveDown, &keys) {
         Direction::Down
     } else {
         return;
@@ -49,53 +94,127 @@ fn select_direction(
 
 pub fn apply_move(
     mut commands: Commands,
-    mut tiles: Query<(Entity, &mut Position, &mut TileKind), With<Tile>>,
+    mut tiles: Query<
+        (
+            Entity,
+            &mut Position,
+            &mut TileKind,
+            &Transform,
+            Option<&TileAnimation>,
+        ),
+        With<Tile>,
+    >,
     mut next_direction: EventReader<Direction>,
     mut new_tile: EventWriter<SpawnTile>,
     mut merged: EventWriter<Merged>,
-    board: Res<Board>,
+    mut applied: EventWriter<MoveApplied>,
+    mut moved: EventWriter<TileMoved>,
+    mut tiles_merged: EventWriter<TilesMerged>,
+    mut game: ResMut<GameState>,
 ) {
     let Some(&direction) = next_direction.read().next() else {
         return;
     };
 
-    let mut positions = vec![0; board.rows * board.columns];
-    tiles
+    let outcome = game.apply(direction);
+    merged.send_batch(outcome.merged.iter().copied().map(Merged::from_power));
+    applied.send(MoveApplied {
+        direction,
+        changed: outcome.changed,
+    });
+    if !outcome.changed {
+        return;
+    }
+    moved.send_batch(
+        outcome
+            .slides
+            .iter()
+            .filter(|slide| slide.from != slide.to)
+            .map(|slide| TileMoved {
+                from: Position::from(slide.from),
+                to: Position::from(slide.to),
+            }),
+    );
+    tiles_merged.send_batch(outcome.merges.iter().map(|merge| TilesMerged {
+        from_a: Position::from(merge.kept),
+        from_b: Position::from(merge.absorbed),
+        into: Position::from(merge.into),
+        value: 2u32.pow(merge.power + 1),
+    }));
+
+    //each entity keeps representing the same tile, so we find them by the cell their tile was in
+    let entities: HashMap<usize, Entity> = tiles
         .iter()
-        .for_each(|(_, pos, kind)| positions[pos.index()] = kind.power());
-
-    use self::tracker::MoveTracker;
-    let mut tracker = MoveTracker::new(board.clone(), positions);
-
-    tracker.start_tracking();
-    let changed = tracker.apply(direction);
-    merged.send_batch(changed);
-
-    if tracker.has_changed() {
-        let tracker_tiles = tracker.tiles();
-        let mut new_tiles = tracker_tiles.into_iter();
-
-        for (entity, mut pos, mut kind) in tiles.iter_mut() {
-            if let Some((new_pos, new_kind)) = new_tiles.next() {
-                *pos = new_pos;
-                *kind = new_kind;
-            } else {
+        .filter(|(.., animation)| !animation.is_some_and(TileAnimation::is_vanishing))
+        .map(|(entity, position, ..)| (position.index(), entity))
+        .collect();
+
+    for TileSlide { from, to } in outcome.slides {
+        let Some((entity, mut position, mut kind, transform, _)) = entities
+            .get(&from)
+            .and_then(|entity| tiles.get_mut(*entity).ok())
+        else {
+            error!("No entity for the tile at {from}, rebuilding them all");
+            for (entity, ..) in &tiles {
                 commands.entity(entity).despawn_recursive();
             }
+            new_tile.send_batch(SpawnTile::mirror(&game));
+            break;
+        };
+
+        //the tile merged into grows, while the other one disappears under it
+        let effect = match outcome
+            .merges
+            .iter()
+            .find(|merge| merge.kept == from || merge.absorbed == from)
+        {
+            Some(merge) if merge.kept == from => {
+                *kind = TileKind::from_power(merge.power + 1);
+                TileEffect::Pop
+            }
+            Some(_) => TileEffect::Vanish,
+            None => TileEffect::None,
+        };
+        let slide_from = (from != to).then_some(transform.translation);
+        if slide_from.is_some() || effect != TileEffect::None {
+            commands
+                .entity(entity)
+                .insert(TileAnimation::new(slide_from, effect));
         }
-
-        //the state remains so we don't start over, the iterator is the same as before
-        for (pos, kind) in new_tiles {
-            new_tile.send(SpawnTile {
-                position: Some(pos),
-                kind: Some(kind),
-            })
+        if from != to {
+            *position = Position::from(to);
         }
+    }
 
+    for _ in 0..game.rules().tiles_per_move {
         new_tile.send(SpawnTile::default());
     }
 }
 
+/// A direction was played, `changed` is false when no tile could move that way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct MoveApplied {
+    pub direction: Direction,
+    pub changed: bool,
+}
+
+/// A tile slid from one cell to another, tiles which merged are reported here too
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct TileMoved {
+    pub from: Position,
+    pub to: Position,
+}
+
+/// Two tiles merged, `from_a` being the one closest to the edge they moved towards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct TilesMerged {
+    pub from_a: Position,
+    pub from_b: Position,
+    pub into: Position,
+    /// Value of the resulting tile
+    pub value: u32,
+}
+
 #[derive(Debug, Clone, Event)]
 pub struct Merged(u32);
 
@@ -108,143 +227,3 @@ impl Merged {
         2u32.pow(self.0)
     }
 }
-
-mod tracker {
-    use crate::{Board, Merged, Position, TileKind};
-
-    use super::Direction;
-
-    #[derive(Debug)]
-    pub struct MoveTracker {
-        board: Board,
-        /// The current state of the board
-        tiles: Vec<usize>,
-        changed: bool,
-    }
-
-    impl MoveTracker {
-        pub fn new(board: Board, tiles: Vec<usize>) -> Self {
-            Self {
-                board,
-                tiles,
-                changed: false,
-            }
-        }
-
-        /// Slice of (`real_position`, `kind`), the `nth` one for `direction`
-        fn stack(&self, direction: Direction, nth: usize) -> Option<Vec<(usize, usize)>> {
-            use Direction::*;
-            if nth >= self.board.rows {
-                None
-            } else {
-                let stack = self
-                    .tiles
-                    .iter()
-                    .enumerate()
-                    //todo move this match outside the closure  so we only check once
-                    .filter(|(idx, _)| match direction {
-                        Left | Right => idx / self.board.columns == nth,
-                        Up | Down => idx % self.board.columns == nth,
-                    })
-                    .map(|(idx, kind)| (idx, *kind));
-
-                let stack = match direction {
-                    Right | Down => stack.rev().collect(),
-                    Left | Up => stack.collect(),
-                };
-
-                Some(stack)
-            }
-        }
-
-        fn transform<F>(&mut self, direction: Direction, mut transformation: F)
-        where
-            //stack -> (stack, changed)
-            F: FnMut(Vec<(usize, usize)>) -> (Vec<(usize, usize)>, bool),
-        {
-            use Direction::*;
-            for i in 0..match direction {
-                Left | Right => self.board.rows,
-                Up | Down => self.board.columns,
-            } {
-                //applying the changes to the current board
-                let (out_stack, changed) = transformation(self.stack(direction, i).unwrap());
-                self.changed |= changed;
-                for (real_pos, kind) in out_stack {
-                    self.tiles[real_pos] = kind
-                }
-            }
-        }
-
-        pub fn apply(&mut self, direction: Direction) -> Vec<Merged> {
-            //todo re-write so that we can make this a one-pass operation (easy)
-            self.go(direction);
-            let merged = self.merge(direction);
-            self.go(direction);
-            merged
-        }
-
-        fn go(&mut self, direction: Direction) {
-            self.transform(direction, |mut stack| {
-                //first free tile
-                let mut free = 0;
-                //needle tracking the tile currently being operated on
-                let mut cursor = free;
-                //if any change to the board occured
-                let mut changed = false;
-
-                while let Some(&(_, kind)) = stack.get(cursor) {
-                    if kind != 0 {
-                        if stack[cursor].0 != stack[free].0 {
-                            //there's a change only if we actually move the tile from a spot to another instead of in-place
-                            changed = true;
-                        }
-                        //we must set the cursor's tile to zero first because it cursor==free it'd overwrite the data otherwise
-                        stack[cursor].1 = 0; // since the tile was moved there's nothing in its stead
-                        stack[free].1 = kind; // tile is put in the first free position
-
-                        free += 1; // the next free spot is the next position
-                    }
-                    cursor += 1
-                }
-
-                (stack, changed)
-            });
-        }
-
-        fn merge(&mut self, direction: Direction) -> Vec<Merged> {
-            let mut merged = Vec::new();
-            self.transform(direction, |mut stack| {
-                let mut changed = false;
-                for j in 0..(stack.len() - 1) {
-                    if stack[j].1 != 0 && stack[j].1 == stack[j + 1].1 {
-                        changed = true;
-                        merged.push(Merged::from_power(stack[j].1 as u32));
-                        stack[j].1 += 1; //the tile we merge into, we simply increase the index by one since the value is the power, not the shown value itself
-                        stack[j + 1].1 = 0; //the tile that has now been destroyed for the merge
-                    }
-                }
-                (stack, changed)
-            });
-            merged
-        }
-
-        pub fn tiles(&mut self) -> Vec<(Position, TileKind)> {
-            let mut tiles = vec![];
-            for (pos, &kind) in self.tiles.iter().enumerate() {
-                if kind != 0 {
-                    tiles.push((pos.into(), TileKind::from_power(kind as u32)));
-                }
-            }
-            tiles
-        }
-
-        pub fn has_changed(&self) -> bool {
-            self.changed
-        }
-
-        pub fn start_tracking(&mut self) {
-            self.changed = false;
-        }
-    }
-}
@@ -0,0 +1,478 @@
+use crate::{engine::Direction, *};
+
+use bevy::{prelude::*, time::Stopwatch};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone)]
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaybackControl>()
+            .init_resource::<Recorder>()
+            .add_systems(
+                OnEnter(AppState::InGame),
+                spawn_playback_overlay.run_if(resource_exists::<Playback>()),
+            )
+            .add_systems(OnExit(AppState::InGame), despawn_playback_overlay)
+            .add_systems(
+                Update,
+                (
+                    (tick_recorder, record_move.after(apply_move))
+                        .chain()
+                        .run_if(in_state(AppState::InGame))
+                        .run_if(not_replaying),
+                    (
+                        control_playback_with_keybinds,
+                        drive_playback.in_set(MoveInput),
+                        update_playback_overlay,
+                    )
+                        .chain()
+                        .run_if(in_state(AppState::InGame))
+                        .run_if(resource_exists::<Playback>()),
+                    finish_recording,
+                ),
+            );
+    }
+}
+
+/// Everything needed to play a game again: its seed, its rules and the moves made
+///
+/// Only the moves which changed the board are kept, along with the time elapsed since the previous one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub board: Board,
+    #[serde(default)]
+    pub spawn_rules: SpawnRules,
+    /// Maximum number of undos allowed during the recorded game
+    pub undo_limit: Option<u32>,
+    /// When the game ended, in seconds since the Unix epoch
+    #[serde(default)]
+    pub date: u64,
+    /// One letter per move, in the order they were made
+    directions: String,
+    /// Milliseconds elapsed before each move, since the previous one or the start of the game
+    delays: Vec<u32>,
+}
+
+impl Replay {
+    /// Group under which the replay of every game is stored
+    const GROUP: &'static str = "replays";
+
+    pub fn new(seed: u64, board: Board, spawn_rules: SpawnRules, undo_limit: Option<u32>) -> Self {
+        Replay {
+            seed,
+            board,
+            spawn_rules,
+            undo_limit,
+            date: 0,
+            directions: String::new(),
+            delays: Vec::new(),
+        }
+    }
+
+    /// Whether the replay of any game was stored
+    pub fn any_stored() -> bool {
+        !storage::keys(Self::GROUP).is_empty()
+    }
+
+    /// The replays of the last `count` games played, from the most recent one
+    pub fn load_latest(count: usize) -> Vec<Self> {
+        let mut keys = storage::keys(Self::GROUP);
+        //keys start with the date, whose number of digits won't change for a while
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+        keys.iter()
+            .filter_map(|key| storage::load(key))
+            .take(count)
+            .collect()
+    }
+
+    /// Stores the replay in its own file, named after its date and seed so that it can be shared
+    pub fn store(&self) {
+        storage::store(
+            &format!("{}/{}-{}", Self::GROUP, self.date, self.seed),
+            self,
+        )
+    }
+
+    /// Reads a replay shared as a RON file
+    pub fn from_file(path: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| warn!("Could not read replay {path}: {err}"))
+            .ok()?;
+        let replay: Self = ron::from_str(&contents)
+            .map_err(|err| warn!("Invalid replay {path}: {err}"))
+            .ok()?;
+        replay
+            .spawn_rules
+            .validate(&replay.board)
+            .map_err(|err| warn!("Invalid replay {path}: {err}"))
+            .ok()?;
+        Some(replay)
+    }
+
+    /// Number of moves
+    pub fn len(&self) -> usize {
+        self.delays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.delays.is_empty()
+    }
+
+    /// Time elapsed between the start of the game and its last move
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.delays.iter().map(|delay| *delay as u64).sum())
+    }
+
+    pub fn push(&mut self, direction: Direction, delay: u32) {
+        self.directions.push(match direction {
+            Direction::Left => 'L',
+            Direction::Up => 'U',
+            Direction::Right => 'R',
+            Direction::Down => 'D',
+        });
+        self.delays.push(delay);
+    }
+
+    /// Keeps only the first `len` moves
+    pub fn truncate(&mut self, len: usize) {
+        self.directions.truncate(len);
+        self.delays.truncate(len);
+    }
+
+    /// The `nth` move and the delay before it
+    pub fn get(&self, nth: usize) -> Option<(u32, Direction)> {
+        let direction = match self.directions.as_bytes().get(nth)? {
+            b'L' => Direction::Left,
+            b'U' => Direction::Up,
+            b'R' => Direction::Right,
+            b'D' => Direction::Down,
+            other => {
+                warn!("Unknown move `{}` in replay", *other as char);
+                return None;
+            }
+        };
+        Some((self.delays[nth], direction))
+    }
+
+    /// The directions played, in order
+    pub fn directions(&self) -> impl Iterator<Item = Direction> + '_ {
+        (0..self.len()).map_while(|nth| self.get(nth).map(|(_, direction)| direction))
+    }
+
+    /// The game as it was when it started, before any move
+    pub fn new_game(&self) -> GameState {
+        let mut game = GameState::with_seed(self.board.clone(), self.seed)
+            .with_rules(self.spawn_rules.clone());
+        game.start();
+        game
+    }
+
+    /// The game as it was after its first `moves` moves
+    pub fn game_after(&self, moves: usize) -> GameState {
+        let mut game = self.new_game();
+        for direction in self.directions().take(moves) {
+            game.play(direction);
+        }
+        game
+    }
+}
+
+/// Records the moves of the game being played
+#[derive(Debug, Resource, Default)]
+pub struct Recorder {
+    replay: Option<Replay>,
+    /// Number of moves of the game when the recorder last looked at it
+    seen: u32,
+    /// Time spent playing since the last move was recorded, which doesn't run while the game is paused
+    clock: Stopwatch,
+}
+
+impl Recorder {
+    /// The recording, up to the `moves`th move
+    ///
+    /// Moves that were undone are only forgotten once a new one is played, so that they can be redone.
+    pub fn replay(&self, moves: u32) -> Option<Replay> {
+        let mut replay = self.replay.clone()?;
+        replay.truncate(moves as usize);
+        Some(replay)
+    }
+}
+
+/// A replay being played back instead of the player's moves
+#[derive(Debug, Resource)]
+pub struct Playback {
+    replay: Replay,
+    /// Index of the next move to play
+    next: usize,
+    /// How much faster than the recording moves are played
+    pub speed: f32,
+    pub paused: bool,
+    /// Milliseconds elapsed since the last move, at playback speed
+    elapsed: f32,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            replay,
+            next: 0,
+            speed: 1.0,
+            paused: false,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+/// Path of a replay given on the command line with `--replay <path>`
+#[derive(Debug, Resource, Default)]
+pub struct ReplayPath(pub Option<String>);
+
+impl ReplayPath {
+    /// The replay given on the command line, if any
+    pub fn load(&self) -> Option<Replay> {
+        Replay::from_file(self.0.as_deref()?)
+    }
+}
+
+/// Run condition for everything which only makes sense when the player is the one making the moves
+pub fn not_replaying(playback: Option<Res<Playback>>) -> bool {
+    playback.is_none()
+}
+
+/// Starts recording the game that was just set up, or continues the recording of a resumed one
+pub fn start_recording(
+    mut recorder: ResMut<Recorder>,
+    game: Res<GameState>,
+    rules: Res<UndoRules>,
+    resume: Option<Res<ResumeGame>>,
+    playback: Option<Res<Playback>>,
+) {
+    let resumed = resume
+        .as_deref()
+        .and_then(|ResumeGame(saved)| saved.replay.clone());
+    *recorder = Recorder {
+        replay: match (playback, resumed) {
+            //we don't record replays being watched
+            (Some(_), _) => None,
+            (None, Some(replay)) if replay.len() == game.moves() as usize => Some(replay),
+            (None, Some(_)) => {
+                warn!("The saved replay doesn't match the saved game, this game won't be recorded");
+                None
+            }
+            (None, None) if game.moves() == 0 => Some(Replay::new(
+                game.seed(),
+                game.board().clone(),
+                game.rules().clone(),
+                rules.limit,
+            )),
+            (None, None) => None,
+        },
+        seen: game.moves(),
+        clock: Stopwatch::new(),
+    };
+}
+
+/// Only counts the time spent in game, so that pauses and menus don't show in the replay
+fn tick_recorder(mut recorder: ResMut<Recorder>, time: Res<Time>) {
+    recorder.clock.tick(time.delta());
+}
+
+fn record_move(
+    mut recorder: ResMut<Recorder>,
+    mut directions: EventReader<Direction>,
+    game: Res<GameState>,
+) {
+    //only the first direction is applied by `apply_move`
+    let direction = directions.read().next().copied();
+    let moves = game.moves();
+    let Recorder {
+        replay,
+        seen,
+        clock,
+    } = &mut *recorder;
+
+    if let (Some(direction), Some(replay)) = (direction, replay) {
+        if moves == *seen + 1 {
+            //moves past this one were undone
+            replay.truncate(*seen as usize);
+            replay.push(direction, clock.elapsed().as_millis() as u32);
+            clock.reset();
+        }
+    }
+    //moves can also be undone or redone in which case the count changes without a direction
+    *seen = moves;
+}
+
+/// Keeps the replay of every game played, and stops watching replays once they're over
+fn finish_recording(
+    mut commands: Commands,
+    mut finished: EventReader<FinishGame>,
+    recorder: Res<Recorder>,
+    playback: Option<Res<Playback>>,
+    game: Res<GameState>,
+) {
+    let Some(reason) = finished.read().last() else {
+        return;
+    };
+    if playback.is_some() {
+        commands.remove_resource::<Playback>();
+    } else if let Some(mut replay) = recorder.replay(game.moves()).filter(|_| reason.is_over()) {
+        replay.date = unix_time();
+        replay.store();
+    }
+}
+
+/// Controls of the replay being watched, sent from the keyboard or a gamepad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum PlaybackControl {
+    TogglePause,
+    /// Doubles the speed
+    Faster,
+    /// Halves the speed
+    Slower,
+    StepBack,
+    StepForward,
+    ToStart,
+    ToEnd,
+}
+
+/// Sends the [`PlaybackControl`]s bound in the [`Keybinds`]
+///
+/// The move actions step through moves and change the speed, the other controls have actions of their own.
+fn control_playback_with_keybinds(
+    keys: Res<Input<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut controls: EventWriter<PlaybackControl>,
+) {
+    for (action, control) in [
+        (Action::ReplayPause, PlaybackControl::TogglePause),
+        (Action::MoveUp, PlaybackControl::Faster),
+        (Action::MoveDown, PlaybackControl::Slower),
+        (Action::MoveLeft, PlaybackControl::StepBack),
+        (Action::MoveRight, PlaybackControl::StepForward),
+        (Action::ReplayStart, PlaybackControl::ToStart),
+        (Action::ReplayEnd, PlaybackControl::ToEnd),
+    ] {
+        if keybinds.just_pressed(action, &keys) {
+            controls.send(control)
+        }
+    }
+}
+
+/// Plays the replay's moves at their recorded pace, following the [`PlaybackControl`]s
+fn drive_playback(
+    mut commands: Commands,
+    mut playback: ResMut<Playback>,
+    mut directions: EventWriter<Direction>,
+    mut new_tiles: EventWriter<SpawnTile>,
+    mut game: ResMut<GameState>,
+    mut score: ResMut<Score>,
+    mut queue: ResMut<MoveQueue>,
+    mut controls: EventReader<PlaybackControl>,
+    time: Res<Time>,
+    tiles: Query<Entity, With<Tile>>,
+) {
+    let mut seek = None;
+    let mut step = false;
+    for control in controls.read() {
+        match control {
+            PlaybackControl::TogglePause => playback.paused = !playback.paused,
+            PlaybackControl::Faster => playback.speed = (playback.speed * 2.0).min(16.0),
+            PlaybackControl::Slower => playback.speed = (playback.speed / 2.0).max(0.25),
+            PlaybackControl::StepBack => {
+                seek = Some(seek.unwrap_or(playback.next).saturating_sub(1))
+            }
+            PlaybackControl::StepForward => step = true,
+            PlaybackControl::ToStart => seek = Some(0),
+            PlaybackControl::ToEnd => seek = Some(playback.replay.len()),
+        }
+    }
+    if let Some(target) = seek {
+        *game = playback.replay.game_after(target);
+        score.0 = game.score();
+        playback.next = target;
+        playback.elapsed = 0.0;
+        queue.0.clear();
+
+        //rebuilding the entities, we don't play a move in the same frame since it would be applied to them
+        for entity in &tiles {
+            commands.entity(entity).despawn_recursive();
+        }
+        new_tiles.send_batch(SpawnTile::mirror(&game));
+        return;
+    }
+
+    let Some((delay, direction)) = playback.replay.get(playback.next) else {
+        return;
+    };
+    if step {
+        playback.elapsed = 0.0;
+    } else if playback.paused {
+        return;
+    } else {
+        playback.elapsed += time.delta_seconds() * 1000.0 * playback.speed;
+        if playback.elapsed < delay as f32 {
+            return;
+        }
+        playback.elapsed -= delay as f32;
+    }
+    playback.next += 1;
+    directions.send(direction);
+}
+
+#[derive(Debug, Component)]
+struct PlaybackOverlay;
+
+fn spawn_playback_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        PlaybackOverlay,
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                    font_size: 24.0,
+                    color: Color::BLACK,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(8.0),
+                bottom: Val::Px(8.0),
+                ..default()
+            },
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
+            ..default()
+        },
+    ));
+}
+
+fn update_playback_overlay(
+    playback: Res<Playback>,
+    mut overlay: Query<&mut Text, With<PlaybackOverlay>>,
+) {
+    let Ok(mut text) = overlay.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = format!(
+        "Replay {}/{} at {}x{}",
+        playback.next,
+        playback.replay.len(),
+        playback.speed,
+        if playback.paused { " (paused)" } else { "" }
+    );
+}
+
+fn despawn_playback_overlay(mut commands: Commands, overlay: Query<Entity, With<PlaybackOverlay>>) {
+    for entity in &overlay {
+        commands.entity(entity).despawn_recursive()
+    }
+}
@@ -0,0 +1,133 @@
+use crate::*;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone)]
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Setup), restore_win_target)
+            .add_systems(
+                PostUpdate,
+                autosave
+                    .after(spawn_tile)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(not_replaying),
+            )
+            .add_systems(Update, forget_finished_game.run_if(not_replaying));
+    }
+}
+
+/// A game in progress, as kept between sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    /// Board dimensions, tiles, score, seed and move count
+    pub game: GameState,
+    pub score: u32,
+    pub congratulation: Congratulation,
+    /// Tile the game is played for
+    #[serde(default)]
+    pub target: WinTarget,
+    /// Recording of the moves played so far
+    #[serde(default)]
+    pub replay: Option<Replay>,
+    /// Number of moves taken back so far
+    #[serde(default)]
+    pub undos_used: u32,
+    /// Number of moves the AI made so far
+    #[serde(default)]
+    pub ai_moves: u32,
+    /// Number of hints asked for so far
+    #[serde(default)]
+    pub hints_used: u32,
+}
+
+impl SavedGame {
+    const KEY: &'static str = "saved_game";
+
+    /// The saved game, if there is one which can be continued
+    pub fn load() -> Option<Self> {
+        let saved: Self = storage::load(Self::KEY)?;
+        match saved.validate() {
+            Ok(()) => Some(saved),
+            Err(err) => {
+                warn!("Ignoring the saved game: {err}");
+                None
+            }
+        }
+    }
+
+    /// Checks that the game can be continued, saves edited by hand or from older versions may not match their board
+    pub fn validate(&self) -> Result<(), String> {
+        let board = self.game.board();
+        if board.size() == 0 {
+            return Err("the board is empty".to_string());
+        }
+        if self.game.cells().len() != board.size() {
+            return Err(format!(
+                "{} cells don't fill a {}x{} board",
+                self.game.cells().len(),
+                board.columns,
+                board.rows
+            ));
+        }
+        self.game.rules().validate(board)
+    }
+
+    pub fn exists() -> bool {
+        storage::exists(Self::KEY)
+    }
+
+    pub fn store(&self) {
+        storage::store(Self::KEY, self)
+    }
+
+    pub fn remove() {
+        storage::remove(Self::KEY)
+    }
+}
+
+/// Game restored on the next [`AppState::Setup`] instead of starting a new one
+#[derive(Debug, Resource)]
+pub struct ResumeGame(pub SavedGame);
+
+/// Saves the game after every change so it survives the window being closed
+fn autosave(
+    game: Res<GameState>,
+    score: Res<Score>,
+    congratulation: Res<Congratulation>,
+    target: Res<WinTarget>,
+    recorder: Res<Recorder>,
+    history: Res<History>,
+) {
+    //asking for a hint only changes the history
+    if game.is_changed() || history.is_changed() {
+        SavedGame {
+            game: game.clone(),
+            score: score.0,
+            congratulation: *congratulation,
+            target: *target,
+            replay: recorder.replay(game.moves()),
+            undos_used: history.undos_used(),
+            ai_moves: history.ai_moves(),
+            hints_used: history.hints_used(),
+        }
+        .store()
+    }
+}
+
+/// A resumed game is played for the tile it was started for
+fn restore_win_target(mut target: ResMut<WinTarget>, resume: Option<Res<ResumeGame>>) {
+    if let Some(ResumeGame(saved)) = resume.as_deref() {
+        *target = saved.target;
+    }
+}
+
+/// A game that is over or was restarted can't be continued, however one that was quit can
+fn forget_finished_game(mut finished: EventReader<FinishGame>) {
+    if finished.read().any(FinishGame::is_over) {
+        SavedGame::remove()
+    }
+}
@@ -0,0 +1,205 @@
+use crate::*;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone)]
+pub struct ScoresPlugin;
+
+impl Plugin for ScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BestScores::load())
+            .insert_resource(Leaderboard::load())
+            .init_resource::<NewPersonalBest>()
+            .add_systems(
+                Update,
+                //the game over menus tell whether the game made it, so it must be known before they're shown
+                (record_best_score, record_leaderboard_entry)
+                    .after(detect_stale_board)
+                    .run_if(not_replaying),
+            );
+    }
+}
+
+/// Best score reached on each board size
+///
+/// Assisted games aren't taken into account since their scores can't be compared with others.
+#[derive(Debug, Resource, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BestScores(HashMap<Board, u32>);
+
+impl BestScores {
+    const KEY: &'static str = "best_scores";
+
+    pub fn load() -> Self {
+        storage::load(Self::KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        storage::store(Self::KEY, self)
+    }
+
+    /// Best score on `board`, zero if no game was played on it yet
+    pub fn get(&self, board: &Board) -> u32 {
+        self.0.get(board).copied().unwrap_or_default()
+    }
+
+    /// Keeps `score` if it beats the best one on `board`, returning whether it did
+    pub fn submit(&mut self, board: &Board, score: u32) -> bool {
+        let best = self.0.entry(board.clone()).or_default();
+        if score > *best {
+            *best = score;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Whether the game that just ended beat the best score on its board
+#[derive(Debug, Resource, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NewPersonalBest(pub bool);
+
+/// Keeps the score of the game which ended if it's the best on its board, those the player quit don't count yet
+fn record_best_score(
+    mut finished: EventReader<FinishGame>,
+    mut best_scores: ResMut<BestScores>,
+    mut new_best: ResMut<NewPersonalBest>,
+    game: Res<GameState>,
+    score: Res<Score>,
+    history: Res<History>,
+) {
+    let Some(reason) = finished.read().last() else {
+        return;
+    };
+    if !reason.is_over() {
+        return;
+    }
+    new_best.0 = !history.is_assisted() && best_scores.submit(game.board(), score.0);
+    if new_best.0 {
+        best_scores.store();
+    }
+}
+
+/// A game kept in the [`Leaderboard`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    /// Value of the largest tile made
+    pub largest_tile: u32,
+    pub moves: u32,
+    /// Time spent playing, up to the last move
+    pub duration: Duration,
+    /// When the game ended, in seconds since the Unix epoch
+    pub date: u64,
+    pub seed: u64,
+    pub board: Board,
+    pub rules: SpawnRules,
+    /// Number of hints asked for
+    #[serde(default)]
+    pub hints: u32,
+}
+
+impl LeaderboardEntry {
+    /// Name of the rules the game was played with
+    pub fn mode(&self) -> &'static str {
+        self.rules.preset_name().unwrap_or("Custom")
+    }
+}
+
+/// Best games ever played, from the highest score to the lowest
+///
+/// Only the [`Leaderboard::KEPT`] best games of each board and rules are kept, so that games on small boards aren't
+/// pushed out by those on large ones. As with the [`BestScores`], assisted games aren't entered.
+#[derive(Debug, Resource, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Leaderboard(Vec<LeaderboardEntry>);
+
+impl Leaderboard {
+    const KEY: &'static str = "leaderboard";
+    /// Number of games kept for each board and rules
+    pub const KEPT: usize = 10;
+
+    pub fn load() -> Self {
+        storage::load(Self::KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        storage::store(Self::KEY, self)
+    }
+
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.0
+    }
+
+    /// Enters `entry`, returning whether it made it to the leaderboard
+    pub fn submit(&mut self, entry: LeaderboardEntry) -> bool {
+        let rank = self.0.partition_point(|other| other.score >= entry.score);
+        let kept = self.0[..rank]
+            .iter()
+            .filter(|other| other.board == entry.board && other.rules == entry.rules)
+            .count();
+        if kept >= Self::KEPT {
+            return false;
+        }
+        self.0.insert(rank, entry);
+
+        //the lowest game of the same board and rules may have been pushed out
+        let mut seen = 0;
+        let LeaderboardEntry { board, rules, .. } = self.0[rank].clone();
+        self.0.retain(|other| {
+            if other.board != board || other.rules != rules {
+                return true;
+            }
+            seen += 1;
+            seen <= Self::KEPT
+        });
+        true
+    }
+}
+
+/// Seconds elapsed since the Unix epoch
+#[cfg(not(target_family = "wasm"))]
+pub fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Seconds elapsed since the Unix epoch
+#[cfg(target_family = "wasm")]
+pub fn unix_time() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Enters the games which ended in the [`Leaderboard`], those the player quit to resume them later don't count
+fn record_leaderboard_entry(
+    mut finished: EventReader<FinishGame>,
+    mut leaderboard: ResMut<Leaderboard>,
+    game: Res<GameState>,
+    score: Res<Score>,
+    history: Res<History>,
+    recorder: Res<Recorder>,
+) {
+    let Some(reason) = finished.read().last() else {
+        return;
+    };
+    if !reason.is_over() || history.is_assisted() {
+        return;
+    }
+    let entry = LeaderboardEntry {
+        score: score.0,
+        largest_tile: 2u32.pow(game.highest_power() as u32),
+        moves: game.moves(),
+        duration: recorder
+            .replay(game.moves())
+            .map_or(Duration::ZERO, |replay| replay.duration()),
+        date: unix_time(),
+        seed: game.seed(),
+        board: game.board().clone(),
+        rules: game.rules().clone(),
+        hints: history.hints_used(),
+    };
+    if leaderboard.submit(entry) {
+        leaderboard.store();
+    }
+}
@@ -1,31 +1,393 @@
-use bevy::prelude::*;
+use crate::*;
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone)]
 pub struct UserSettingsPlugin;
 
 impl Plugin for UserSettingsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Keybinds>();
+        let settings = SettingsFile::load();
+        app.insert_resource(settings.keybinds)
+            .insert_resource(settings.preferences)
+            .add_systems(
+                Update,
+                (
+                    apply_window_mode.run_if(resource_changed::<Preferences>()),
+                    save_settings,
+                ),
+            );
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Resource)]
+/// Keys bound to each [`Action`], any of them triggers it
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Keybinds {
-    pub move_left: KeyCode,
-    pub move_up: KeyCode,
-    pub move_right: KeyCode,
-    pub move_down: KeyCode,
-    pub pause_game: KeyCode,
+    pub move_left: Vec<KeyCode>,
+    pub move_up: Vec<KeyCode>,
+    pub move_right: Vec<KeyCode>,
+    pub move_down: Vec<KeyCode>,
+    pub pause_game: Vec<KeyCode>,
+    pub undo: Vec<KeyCode>,
+    pub redo: Vec<KeyCode>,
+    pub restart: Vec<KeyCode>,
+    pub toggle_ai: Vec<KeyCode>,
+    pub hint: Vec<KeyCode>,
+    pub replay_pause: Vec<KeyCode>,
+    pub replay_start: Vec<KeyCode>,
+    pub replay_end: Vec<KeyCode>,
 }
 
 impl Default for Keybinds {
     fn default() -> Self {
+        Self::with_moves([KeyCode::Left, KeyCode::Up, KeyCode::Right, KeyCode::Down])
+    }
+}
+
+impl Keybinds {
+    /// Bindings moving with the given left, up, right and down keys, along with the usual keys for other actions
+    fn with_moves([left, up, right, down]: [KeyCode; 4]) -> Self {
         Self {
-            move_left: KeyCode::Left,
-            move_up: KeyCode::Up,
-            move_right: KeyCode::Right,
-            move_down: KeyCode::Down,
-            pause_game: KeyCode::Escape,
+            move_left: vec![left],
+            move_up: vec![up],
+            move_right: vec![right],
+            move_down: vec![down],
+            pause_game: vec![KeyCode::Escape, KeyCode::P],
+            undo: vec![KeyCode::Z],
+            redo: vec![KeyCode::Y],
+            restart: vec![KeyCode::R],
+            toggle_ai: vec![KeyCode::I],
+            hint: vec![KeyCode::T],
+            replay_pause: vec![KeyCode::Space],
+            replay_start: vec![KeyCode::Home],
+            replay_end: vec![KeyCode::End],
+        }
+    }
+
+    /// Named sets of bindings
+    pub fn presets() -> [(&'static str, Keybinds); 4] {
+        [
+            ("Arrows", Self::default()),
+            (
+                "WASD",
+                Self::with_moves([KeyCode::A, KeyCode::W, KeyCode::D, KeyCode::S]),
+            ),
+            (
+                "HJKL",
+                Self::with_moves([KeyCode::H, KeyCode::K, KeyCode::L, KeyCode::J]),
+            ),
+            (
+                "Numpad",
+                Self::with_moves([
+                    KeyCode::Numpad4,
+                    KeyCode::Numpad8,
+                    KeyCode::Numpad6,
+                    KeyCode::Numpad2,
+                ]),
+            ),
+        ]
+    }
+
+    /// Name of the preset these bindings are, if any
+    pub fn preset_name(&self) -> Option<&'static str> {
+        Self::presets()
+            .into_iter()
+            .find(|(_, preset)| preset == self)
+            .map(|(name, _)| name)
+    }
+
+    /// Keys bound to `action`
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        match action {
+            Action::MoveLeft => &self.move_left,
+            Action::MoveUp => &self.move_up,
+            Action::MoveRight => &self.move_right,
+            Action::MoveDown => &self.move_down,
+            Action::PauseGame => &self.pause_game,
+            Action::Undo => &self.undo,
+            Action::Redo => &self.redo,
+            Action::Restart => &self.restart,
+            Action::ToggleAi => &self.toggle_ai,
+            Action::Hint => &self.hint,
+            Action::ReplayPause => &self.replay_pause,
+            Action::ReplayStart => &self.replay_start,
+            Action::ReplayEnd => &self.replay_end,
+        }
+    }
+
+    fn keys_mut(&mut self, action: Action) -> &mut Vec<KeyCode> {
+        match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveUp => &mut self.move_up,
+            Action::MoveRight => &mut self.move_right,
+            Action::MoveDown => &mut self.move_down,
+            Action::PauseGame => &mut self.pause_game,
+            Action::Undo => &mut self.undo,
+            Action::Redo => &mut self.redo,
+            Action::Restart => &mut self.restart,
+            Action::ToggleAi => &mut self.toggle_ai,
+            Action::Hint => &mut self.hint,
+            Action::ReplayPause => &mut self.replay_pause,
+            Action::ReplayStart => &mut self.replay_start,
+            Action::ReplayEnd => &mut self.replay_end,
+        }
+    }
+
+    /// Action `key` is bound to, if any
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| self.keys(*action).contains(&key))
+    }
+
+    /// Binds `key` to `action`, taking it away from the action it was bound to if any
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        if let Some(previous) = self.action(key) {
+            self.unbind(previous, key);
+        }
+        self.keys_mut(action).push(key);
+    }
+
+    pub fn unbind(&mut self, action: Action, key: KeyCode) {
+        self.keys_mut(action).retain(|bound| *bound != key);
+    }
+
+    /// Whether a key bound to `action` was pressed this frame
+    pub fn just_pressed(&self, action: Action, keys: &Input<KeyCode>) -> bool {
+        keys.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// Whether a key bound to `action` was released this frame
+    pub fn just_released(&self, action: Action, keys: &Input<KeyCode>) -> bool {
+        keys.any_just_released(self.keys(action).iter().copied())
+    }
+}
+
+/// Everything the player can bind keys to
+///
+/// While watching a replay, the move actions step through it and change its speed instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveUp,
+    MoveRight,
+    MoveDown,
+    PauseGame,
+    Undo,
+    Redo,
+    Restart,
+    /// Lets the AI play, or takes the game back from it
+    ToggleAi,
+    /// Shows the move the AI would make
+    Hint,
+    /// Pauses or resumes the replay being watched
+    ReplayPause,
+    /// Goes back to the start of the replay
+    ReplayStart,
+    /// Skips to the end of the replay
+    ReplayEnd,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::MoveLeft,
+        Action::MoveUp,
+        Action::MoveRight,
+        Action::MoveDown,
+        Action::PauseGame,
+        Action::Undo,
+        Action::Redo,
+        Action::Restart,
+        Action::ToggleAi,
+        Action::Hint,
+        Action::ReplayPause,
+        Action::ReplayStart,
+        Action::ReplayEnd,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Left",
+            Action::MoveUp => "Up",
+            Action::MoveRight => "Right",
+            Action::MoveDown => "Down",
+            Action::PauseGame => "Pause",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::Restart => "Restart",
+            Action::ToggleAi => "AI",
+            Action::Hint => "Hint",
+            Action::ReplayPause => "Replay pause",
+            Action::ReplayStart => "Replay start",
+            Action::ReplayEnd => "Replay end",
+        }
+    }
+}
+
+/// The player's preferences, other than the [`Keybinds`]
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Volume of the music, from 0 to 1
+    pub music_volume: f32,
+    /// Board new games are set up with
+    pub board: Board,
+    pub theme: Theme,
+    pub fullscreen: bool,
+    /// How far from its center a gamepad's stick must be pushed to move, from 0 to 1
+    pub stick_dead_zone: f32,
+    /// Distance in pixels a finger or the mouse must travel for a swipe to move the tiles
+    pub swipe_distance: f32,
+    /// Largest angle in degrees between a swipe and the direction it moves the tiles in
+    pub swipe_tolerance: f32,
+    /// Seconds taken by tiles to slide, and then to pop or appear, no animation is played when zero
+    pub animation_duration: f32,
+    /// Player whose progress, such as the achievements unlocked, is kept
+    pub profile: String,
+    /// Moves the [`AiPlayer`] looks ahead at most
+    pub ai_depth: u32,
+    /// Seconds the [`AiPlayer`] may think about each move
+    pub ai_time_budget: f32,
+}
+
+impl Preferences {
+    /// Profile used until players can make their own
+    pub const DEFAULT_PROFILE: &'static str = "default";
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            music_volume: 1.0,
+            board: Board::default(),
+            theme: Theme::Light,
+            fullscreen: false,
+            stick_dead_zone: 0.5,
+            swipe_distance: 50.0,
+            swipe_tolerance: 30.0,
+            animation_duration: 0.1,
+            profile: Self::DEFAULT_PROFILE.to_string(),
+            ai_depth: 4,
+            ai_time_budget: 0.05,
+        }
+    }
+}
+
+/// Colors of the background and of the menus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Light, Theme::Dark];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+        }
+    }
+
+    /// Color behind the board
+    pub fn background(&self) -> Color {
+        match self {
+            Theme::Light => Color::WHITE,
+            Theme::Dark => Color::hex("1f1d1a").unwrap(),
+        }
+    }
+
+    /// Color of the backdrop menus are drawn on
+    pub fn backdrop(&self) -> Color {
+        match self {
+            Theme::Light => Color::rgba(1.0, 1.0, 1.0, 0.8),
+            Theme::Dark => Color::rgba(0.12, 0.11, 0.1, 0.85),
+        }
+    }
+
+    /// Color of the text written straight on the backdrop
+    pub fn text(&self) -> Color {
+        match self {
+            Theme::Light => Color::BLACK,
+            Theme::Dark => Color::hex("f9f6f2").unwrap(),
+        }
+    }
+}
+
+fn apply_window_mode(
+    preferences: Res<Preferences>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+    let mode = if preferences.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+    //setting it anyway would mark the window as changed every time a preference does
+    if window.mode != mode {
+        window.mode = mode;
+    }
+}
+
+/// Contents of the settings file
+///
+/// Settings which are missing from the file take their default value, while files of another version are ignored
+/// altogether.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsFile {
+    /// Bumped whenever the meaning of a setting changes, so that files written before aren't misread
+    pub version: u32,
+    pub keybinds: Keybinds,
+    pub preferences: Preferences,
+}
+
+impl SettingsFile {
+    const KEY: &'static str = "settings";
+    pub const VERSION: u32 = 2;
+
+    fn load() -> Self {
+        storage::load_config::<Self>(Self::KEY)
+            .map(Self::checked)
+            .unwrap_or_default()
+    }
+
+    /// The settings read from the file, or the default ones if it was written for another version
+    pub fn checked(self) -> Self {
+        if self.version == Self::VERSION {
+            return self;
+        }
+        warn!(
+            "Ignoring settings of version {} instead of {}, defaults are used",
+            self.version,
+            Self::VERSION
+        );
+        Self::default()
+    }
+
+    fn store(&self) {
+        storage::store_config(Self::KEY, self)
+    }
+}
+
+fn save_settings(keybinds: Res<Keybinds>, preferences: Res<Preferences>) {
+    //both are added with the values read from the file, there's nothing new to save then
+    if (keybinds.is_changed() && !keybinds.is_added())
+        || (preferences.is_changed() && !preferences.is_added())
+    {
+        SettingsFile {
+            version: SettingsFile::VERSION,
+            keybinds: keybinds.clone(),
+            preferences: preferences.clone(),
         }
+        .store()
     }
 }
@@ -0,0 +1,183 @@
+use crate::{engine::Direction, *};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Copy, Clone)]
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Statistics::load()).add_systems(
+            Update,
+            record_game.after(detect_stale_board).run_if(not_replaying),
+        );
+    }
+}
+
+/// What happened during a game that ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub score: u32,
+    /// Power of the largest tile made
+    pub highest_power: u32,
+    /// Power of the [`WinTarget`] the game was played for
+    pub target: u32,
+    pub won: bool,
+    pub moves: u32,
+    /// Number of merges made for each value of the resulting tile
+    pub merges: BTreeMap<u32, u32>,
+    /// Moves made towards each direction, in the order of [`Direction::ALL`]
+    pub directions: [u32; 4],
+    /// Number of hints asked for
+    #[serde(default)]
+    pub hints: u32,
+}
+
+impl GameRecord {
+    /// Counts the merges and directions of the game played in `replay`
+    ///
+    /// Moves which were undone aren't part of the replay, so they aren't counted either.
+    fn tally(&mut self, replay: &Replay) {
+        let mut game = replay.new_game();
+        for direction in replay.directions() {
+            let outcome = game.play(direction);
+            for power in outcome.merged {
+                *self.merges.entry(2u32.pow(power + 1)).or_default() += 1;
+            }
+            self.directions[direction as usize] += 1;
+        }
+    }
+}
+
+/// Records of every game played to the end, from the first one
+///
+/// Games watched as replays or quit to be resumed later aren't recorded.
+#[derive(Debug, Resource, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Statistics(Vec<GameRecord>);
+
+impl Statistics {
+    const KEY: &'static str = "statistics";
+
+    pub fn load() -> Self {
+        storage::load(Self::KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        storage::store(Self::KEY, self)
+    }
+
+    pub fn games(&self) -> &[GameRecord] {
+        &self.0
+    }
+
+    pub fn push(&mut self, record: GameRecord) {
+        self.0.push(record)
+    }
+
+    pub fn best_score(&self) -> u32 {
+        self.0
+            .iter()
+            .map(|game| game.score)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn average_score(&self) -> f32 {
+        self.average(|game| game.score)
+    }
+
+    /// Number of hints asked for across all games
+    pub fn hints(&self) -> u32 {
+        self.0.iter().map(|game| game.hints).sum()
+    }
+
+    pub fn average_moves(&self) -> f32 {
+        self.average(|game| game.moves)
+    }
+
+    fn average(&self, value: impl Fn(&GameRecord) -> u32) -> f32 {
+        if self.0.is_empty() {
+            return 0.0;
+        }
+        self.0.iter().map(|game| value(game) as f32).sum::<f32>() / self.0.len() as f32
+    }
+
+    /// Games won and played for each target, by power of the target
+    pub fn wins(&self) -> BTreeMap<u32, (u32, u32)> {
+        let mut wins = BTreeMap::new();
+        for game in &self.0 {
+            let (won, played) = wins.entry(game.target).or_insert((0, 0));
+            *won += game.won as u32;
+            *played += 1;
+        }
+        wins
+    }
+
+    /// Number of games for each power of the largest tile they made
+    pub fn highest_powers(&self) -> BTreeMap<u32, u32> {
+        let mut powers = BTreeMap::new();
+        for game in &self.0 {
+            *powers.entry(game.highest_power).or_default() += 1;
+        }
+        powers
+    }
+
+    /// Number of merges across all games, for each value of the resulting tile
+    pub fn merges(&self) -> BTreeMap<u32, u32> {
+        let mut merges = BTreeMap::new();
+        for (value, count) in self.0.iter().flat_map(|game| &game.merges) {
+            *merges.entry(*value).or_default() += count;
+        }
+        merges
+    }
+
+    /// Moves made towards each direction across all games, in the order of [`Direction::ALL`]
+    pub fn directions(&self) -> [(Direction, u32); 4] {
+        let mut directions = Direction::ALL.map(|direction| (direction, 0));
+        for game in &self.0 {
+            for (total, count) in directions.iter_mut().zip(game.directions) {
+                total.1 += count;
+            }
+        }
+        directions
+    }
+}
+
+/// Appends the record of the game that just ended to the [`Statistics`]
+fn record_game(
+    mut finished: EventReader<FinishGame>,
+    mut statistics: ResMut<Statistics>,
+    game: Res<GameState>,
+    score: Res<Score>,
+    target: Res<WinTarget>,
+    history: Res<History>,
+    recorder: Res<Recorder>,
+) {
+    let Some(reason) = finished.read().last() else {
+        return;
+    };
+    if !reason.is_over() {
+        return;
+    }
+    let mut record = GameRecord {
+        score: score.0,
+        highest_power: game.highest_power() as u32,
+        target: target.power,
+        won: target.is_reached(game.highest_power()),
+        moves: game.moves(),
+        merges: BTreeMap::new(),
+        directions: [0; 4],
+        hints: history.hints_used(),
+    };
+    //the moves are played again from the recording, which also holds the ones made before the game was resumed
+    match recorder.replay(game.moves()) {
+        Some(replay) => record.tally(&replay),
+        None => {
+            warn!("This game wasn't recorded, its merges and moves won't count in the statistics")
+        }
+    }
+    statistics.push(record);
+    statistics.store();
+}
@@ -0,0 +1,172 @@
+//! Minimal key-value persistence
+//!
+//! Values are kept as RON files in the platform data directory on native, or its config directory for the user's
+//! settings, and in the browser's `localStorage` on the web.
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads and deserializes the value stored under `key`
+///
+/// Missing values yield `None`, so do values which can't be deserialized anymore, in which case a warning is logged.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    load_from(Directory::Data, key)
+}
+
+/// Serializes `value` and stores it under `key`, logging a warning on failure
+pub fn store<T: Serialize>(key: &str, value: &T) {
+    store_in(Directory::Data, key, value)
+}
+
+/// Like [`load`], for the user's settings
+pub fn load_config<T: DeserializeOwned>(key: &str) -> Option<T> {
+    load_from(Directory::Config, key)
+}
+
+/// Like [`store`], for the user's settings
+pub fn store_config<T: Serialize>(key: &str, value: &T) {
+    store_in(Directory::Config, key, value)
+}
+
+/// Whether anything is stored under `key`
+pub fn exists(key: &str) -> bool {
+    backend::read(Directory::Data, key).is_some()
+}
+
+pub fn remove(key: &str) {
+    backend::remove(Directory::Data, key)
+}
+
+/// Keys of the values stored under `group`, as in `group/name`, in no particular order
+pub fn keys(group: &str) -> Vec<String> {
+    backend::names(Directory::Data, group)
+        .into_iter()
+        .map(|name| format!("{group}/{name}"))
+        .collect()
+}
+
+/// Where values are kept on native platforms, they all share the same `localStorage` on the web
+#[derive(Debug, Clone, Copy)]
+enum Directory {
+    Data,
+    Config,
+}
+
+fn load_from<T: DeserializeOwned>(directory: Directory, key: &str) -> Option<T> {
+    let contents = backend::read(directory, key)?;
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Ignoring corrupted `{key}` data: {err}");
+            None
+        }
+    }
+}
+
+fn store_in<T: Serialize>(directory: Directory, key: &str, value: &T) {
+    let config = ron::ser::PrettyConfig::default().compact_arrays(true);
+    let contents = match ron::ser::to_string_pretty(value, config) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("Could not serialize `{key}`: {err}");
+            return;
+        }
+    };
+    if let Err(err) = backend::write(directory, key, &contents) {
+        warn!("Could not store `{key}`: {err}");
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+mod backend {
+    use super::Directory;
+    use directories::ProjectDirs;
+    use std::{fs, path::PathBuf};
+
+    fn root(directory: Directory) -> Option<PathBuf> {
+        let dirs = ProjectDirs::from("", "s0lst1ce", "b2048")?;
+        Some(match directory {
+            Directory::Data => dirs.data_dir().to_path_buf(),
+            Directory::Config => dirs.config_dir().to_path_buf(),
+        })
+    }
+
+    fn path(directory: Directory, key: &str) -> Option<PathBuf> {
+        Some(root(directory)?.join(format!("{key}.ron")))
+    }
+
+    pub fn read(directory: Directory, key: &str) -> Option<String> {
+        fs::read_to_string(path(directory, key)?).ok()
+    }
+
+    pub fn write(directory: Directory, key: &str, contents: &str) -> Result<(), String> {
+        let path = path(directory, key).ok_or("no home directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(path, contents).map_err(|err| err.to_string())
+    }
+
+    pub fn remove(directory: Directory, key: &str) {
+        if let Some(path) = path(directory, key) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// Names of the files in the `group` subdirectory, without their extension
+    pub fn names(directory: Directory, group: &str) -> Vec<String> {
+        let Some(entries) = root(directory).and_then(|root| fs::read_dir(root.join(group)).ok())
+        else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                (path.extension()? == "ron")
+                    .then(|| path.file_stem()?.to_str().map(String::from))?
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_family = "wasm")]
+mod backend {
+    use super::Directory;
+
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    fn item(key: &str) -> String {
+        format!("b2048.{key}")
+    }
+
+    pub fn read(_: Directory, key: &str) -> Option<String> {
+        storage()?.get_item(&item(key)).ok()?
+    }
+
+    pub fn write(_: Directory, key: &str, contents: &str) -> Result<(), String> {
+        storage()
+            .ok_or("`localStorage` is unavailable")?
+            .set_item(&item(key), contents)
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    pub fn remove(_: Directory, key: &str) {
+        if let Some(storage) = storage() {
+            let _ = storage.remove_item(&item(key));
+        }
+    }
+
+    /// Names of the items whose key starts with `group/`
+    pub fn names(_: Directory, group: &str) -> Vec<String> {
+        let Some(storage) = storage() else {
+            return Vec::new();
+        };
+        let prefix = item(&format!("{group}/"));
+        (0..storage.length().unwrap_or_default())
+            .filter_map(|idx| storage.key(idx).ok()?)
+            .filter_map(|key| key.strip_prefix(&prefix).map(String::from))
+            .collect()
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{engine::Direction, *};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+#[derive(Debug, Copy, Clone)]
+pub struct SwipePlugin;
+
+impl Plugin for SwipePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            swipe
+                .in_set(MoveInput)
+                .run_if(in_state(AppState::InGame))
+                .run_if(not_replaying),
+        );
+    }
+}
+
+/// Direction of a swipe going from `start` to `end`, in window coordinates
+///
+/// Swipes shorter than `min_distance` or further than `tolerance` degrees away from every direction are ignored.
+pub fn swipe_direction(
+    start: Vec2,
+    end: Vec2,
+    min_distance: f32,
+    tolerance: f32,
+) -> Option<Direction> {
+    let delta = end - start;
+    if delta.length() < min_distance {
+        return None;
+    }
+    let horizontal = delta.x.abs() > delta.y.abs();
+    let (major, minor) = if horizontal {
+        (delta.x.abs(), delta.y.abs())
+    } else {
+        (delta.y.abs(), delta.x.abs())
+    };
+    if minor.atan2(major).to_degrees() > tolerance {
+        return None;
+    }
+    //window coordinates grow downwards
+    Some(match (horizontal, delta.x < 0.0, delta.y < 0.0) {
+        (true, true, _) => Direction::Left,
+        (true, false, _) => Direction::Right,
+        (false, _, true) => Direction::Up,
+        (false, _, false) => Direction::Down,
+    })
+}
+
+/// Moves the tiles when the board is swiped, with a finger or by dragging the mouse
+fn swipe(
+    touches: Res<Touches>,
+    mouse: Res<Input<MouseButton>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    preferences: Res<Preferences>,
+    mut directions: EventWriter<Direction>,
+    //where the mouse was when its button was pressed
+    mut drag_start: Local<Option<Vec2>>,
+) {
+    let cursor = window.get_single().ok().and_then(Window::cursor_position);
+    if mouse.just_pressed(MouseButton::Left) {
+        *drag_start = cursor;
+    }
+
+    let mut swipes: Vec<_> = touches
+        .iter_just_released()
+        .map(|touch| (touch.start_position(), touch.position()))
+        .collect();
+    if mouse.just_released(MouseButton::Left) {
+        if let (Some(start), Some(end)) = (drag_start.take(), cursor) {
+            swipes.push((start, end));
+        }
+    }
+
+    //only one move is applied per frame, just like with the keyboard
+    if let Some(direction) = swipes.into_iter().find_map(|(start, end)| {
+        swipe_direction(
+            start,
+            end,
+            preferences.swipe_distance,
+            preferences.swipe_tolerance,
+        )
+    }) {
+        directions.send(direction)
+    }
+}
@@ -1,15 +1,6 @@
 use crate::*;
 
-use bevy::{
-    prelude::*,
-    render::render_resource::TextureDescriptor,
-    window::{PrimaryWindow, WindowResized},
-};
-
-use rand::{
-    seq::{IteratorRandom, SliceRandom},
-    thread_rng,
-};
+use bevy::{prelude::*, render::render_resource::TextureDescriptor, window::WindowResized};
 
 #[derive(Debug, Resource, PartialEq, Clone)]
 pub struct Tiling {
@@ -24,7 +15,21 @@ pub struct Tiling {
     /// Scaling
     horizontal_scale: f32,
     vertical_scale: f32,
+    /// How far below the center of the window the center of the board is, to make room for the HUD
+    vertical_offset: f32,
+}
+impl Tiling {
+    /// Scale of the tiles' sprites
+    pub fn scale(&self) -> Vec3 {
+        Vec3::new(self.horizontal_scale, self.vertical_scale, 1.0)
+    }
+
+    /// Width and height of a tile as shown on screen
+    pub fn tile_size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
 }
+
 impl Default for Tiling {
     fn default() -> Self {
         Tiling {
@@ -34,6 +39,7 @@ impl Default for Tiling {
             height: 0.0,
             horizontal_spacing: 0.0,
             vertical_spacing: 0.0,
+            vertical_offset: 0.0,
         }
     }
 }
@@ -46,12 +52,13 @@ struct TileBundle {
     position: Position,
     kind: TileKind,
     sprite: SpriteSheetBundle,
+    animation: TileAnimation,
     _tile: Tile,
 }
 
 ///The position of the tile of the board
 ///
-/// Underlying implementation currently makes it an index. For a board of size (4x4), Position(5) is first column second row.
+/// Underlying implementation currently makes it an index. For a board of size (4x4), Position(5) is second column second row.
 #[derive(Component, Debug, PartialEq, PartialOrd, Ord, Eq, Copy, Clone)]
 pub struct Position(usize);
 
@@ -62,24 +69,27 @@ impl From<usize> for Position {
 }
 
 impl Position {
-    fn to_translation(self, tiling: &Tiling, board: &Board, window: &Window) -> Vec3 {
+    pub fn to_translation(self, tiling: &Tiling, board: &Board) -> Vec3 {
         let mut translation = Vec3::ZERO;
         let row = self.0 / board.columns; //for integers `/` is a floor division
-        let col = self.0 % board.rows;
+        let col = self.0 % board.columns;
 
-        // we set the abscissa to as many times the width of a tile as there are tiles before (on the left, ie the row number)
-        // to this we add the spacing, but multiply it by one less because we don't account for the space *after* the tile we're placing
-        // this would otherwise place the tile too far and cause issues on the right-end side of the board
-        // then we finally add half the size of the tile itself because the origin is the center of the tile
-        // because the origin of the window is also its center, we offset it similarly
-        translation.x = col as f32 * tiling.width
-            + col.saturating_sub(1) as f32 * tiling.horizontal_spacing
-            - (window.width() - tiling.width) / 2.0;
+        // we set the abscissa to as many times the width of a tile and its spacing as there are tiles before (on the left, ie the column number)
+        // then we add half the size of the tile itself because the origin is the center of the tile
+        // because the origin of the window is also its center, we offset it by half the width of the whole board
+        let board_width = board.columns as f32 * tiling.width
+            + board.columns.saturating_sub(1) as f32 * tiling.horizontal_spacing;
+        translation.x = col as f32 * (tiling.width + tiling.horizontal_spacing)
+            + tiling.width / 2.0
+            - board_width / 2.0;
 
         // same as above but for the ordinates, signs are opposed because up is positive
-        translation.y = -(row as f32 * tiling.height)
-            - row.saturating_sub(1) as f32 * tiling.vertical_spacing
-            + (window.height() - tiling.height) / 2.0;
+        let board_height = board.rows as f32 * tiling.height
+            + board.rows.saturating_sub(1) as f32 * tiling.vertical_spacing;
+        translation.y = -(row as f32 * (tiling.height + tiling.vertical_spacing))
+            - tiling.height / 2.0
+            + board_height / 2.0
+            - tiling.vertical_offset;
 
         translation
     }
@@ -89,20 +99,12 @@ impl Position {
     }
 }
 
-#[derive(Resource, Debug, Clone)]
-pub struct Board {
-    pub columns: usize,
-    pub rows: usize,
-}
+impl Resource for Board {}
 
-impl Default for Board {
-    fn default() -> Self {
-        Board {
-            columns: 4,
-            rows: 4,
-        }
-    }
-}
+impl Resource for GameState {}
+
+/// Rules of the next game, the game in progress keeps its own in [`GameState::rules`]
+impl Resource for SpawnRules {}
 
 ///Represents the value of a tile.
 ///
@@ -121,15 +123,6 @@ impl TileKind {
     pub fn power(&self) -> usize {
         self.0 as usize
     }
-
-    fn new_tile_value() -> TileKind {
-        let mut rng = thread_rng();
-        let pow = [(1, 7), (2, 3)]
-            .choose_weighted(&mut rng, |(_, weight)| *weight)
-            .unwrap();
-
-        TileKind(pow.0)
-    }
 }
 
 #[derive(Event, Debug, Clone, Copy, Default)]
@@ -138,82 +131,97 @@ pub struct SpawnTile {
     pub kind: Option<TileKind>,
 }
 
+/// A tile new to the game was spawned
+///
+/// Tiles recreated from the [`GameState`], when a move is undone or a game resumed, aren't reported.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileSpawned {
+    pub position: Position,
+    pub kind: TileKind,
+}
+
+impl SpawnTile {
+    /// Events recreating every tile of `game`, for when the entities must be rebuilt from scratch
+    pub fn mirror(game: &GameState) -> impl Iterator<Item = SpawnTile> + '_ {
+        game.tiles().map(|(idx, power)| SpawnTile {
+            position: Some(Position(idx)),
+            kind: Some(TileKind::from_power(power as u32)),
+        })
+    }
+}
+
 pub fn spawn_tile(
     mut commands: Commands,
-    tiles: Query<&Position, With<Tile>>,
-    board: Res<Board>,
+    mut game: ResMut<GameState>,
     tiling: Res<Tiling>,
+    board: Res<Board>,
     mut new_tiles: EventReader<SpawnTile>,
-    mut game_over: EventWriter<FinishGame>,
+    mut spawned_events: EventWriter<TileSpawned>,
     tiles_atlas: Res<TilesAtlas>,
 ) {
     if new_tiles.is_empty() {
         return;
     }
-    let mut positions = vec![];
-    let mut values = vec![];
+    let requests: Vec<_> = new_tiles.read().copied().collect();
+    let mut spawned = Vec::with_capacity(requests.len());
 
-    let mut random = 0;
-    for SpawnTile { position, kind } in new_tiles.read() {
+    //tiles with a known position are placed first so random ones can't take their spot
+    for SpawnTile { position, kind } in &requests {
         if let Some(pos) = position {
-            positions.push(*pos);
-        } else {
-            random += 1
-        }
-        if let Some(value) = kind {
-            values.push(*value);
+            let new = kind.is_none();
+            let kind = kind.unwrap_or_else(|| TileKind(game.random_power() as u32));
+            game.place(pos.index(), kind.power());
+            spawned.push((*pos, kind));
+            //a tile whose position and kind are both known was already part of the game
+            if new {
+                spawned_events.send(TileSpawned {
+                    position: *pos,
+                    kind,
+                });
+            }
         }
     }
 
-    //finding free tiles
-    let mut occupied = Vec::with_capacity(board.columns * board.rows);
-
-    tiles
-        .iter()
-        .chain(positions.iter())
-        .for_each(|pos| occupied.push(pos));
-
-    //we make sure there's enough space to spawn all the tiles necessary
-    if occupied.capacity() - occupied.len() < random {
-        game_over.send(FinishGame::GameOver);
-        return;
-    }
-
-    //we add just enough random positions
-    let mut rng = thread_rng();
-    positions.extend(
-        (0..(board.columns * board.rows))
-            .map(Position)
-            .filter(|pos| !occupied.contains(&pos))
-            .choose_multiple(&mut rng, random),
-    );
-
-    //we create missing tile values for to push
-    while values.len() < positions.len() {
-        values.push(TileKind::new_tile_value())
+    //when the rules spawn several tiles per move the board may not have room for all of them, in which case the
+    //extra ones are dropped: whether the game is over is up to `detect_stale_board`
+    for SpawnTile { kind, .. } in requests.iter().filter(|tile| tile.position.is_none()) {
+        let Some(idx) = game.random_empty_cell() else {
+            break;
+        };
+        let kind = kind.unwrap_or_else(|| TileKind(game.random_power() as u32));
+        game.place(idx, kind.power());
+        spawned.push((Position(idx), kind));
+        spawned_events.send(TileSpawned {
+            position: Position(idx),
+            kind,
+        });
     }
-    let mut value = values.into_iter();
 
-    //choosing where to create the new tile if possible
+    //creating the entities mirroring the new tiles
+    //they grow in from nothing once the other tiles stopped sliding
     let atlas_handle = tiles_atlas.0.clone();
-    let (abscissa, ordinate) = (tiling.horizontal_scale, tiling.vertical_scale);
-    commands.spawn_batch(positions.into_iter().map(move |pos| {
-        let kind = value.next().unwrap();
-        TileBundle {
+    let translations: Vec<_> = spawned
+        .iter()
+        .map(|(pos, _)| pos.to_translation(&tiling, &board))
+        .collect();
+    commands.spawn_batch(spawned.into_iter().zip(translations).map(
+        move |((pos, kind), translation)| TileBundle {
             position: pos,
             kind,
             sprite: SpriteSheetBundle {
                 transform: Transform {
-                    scale: Vec3::new(abscissa, ordinate, 1.0),
+                    translation,
+                    scale: Vec3::ZERO,
                     ..default()
                 },
-                sprite: TextureAtlasSprite::new(kind.power()),
+                sprite: TextureAtlasSprite::new(kind.power().min(TileHandles::TILES.len() - 1)),
                 texture_atlas: atlas_handle.clone(),
                 ..default()
             },
+            animation: TileAnimation::new(None, TileEffect::Grow),
             _tile: Tile,
-        }
-    }));
+        },
+    ));
 }
 
 fn update_value(
@@ -226,30 +234,24 @@ fn update_value(
         let index = atlas
             .get(&tiles_atlas.0)
             .expect("`TilesAtlas` should be set at this point")
-            .get_texture_index(&tile_handles.0[kind.power()])
+            .get_texture_index(tile_handles.tile(kind.power()))
             .unwrap();
         *sprite = TextureAtlasSprite::new(index)
     }
 }
 
-fn move_tiles(
+pub fn move_tiles(
     mut tiles: Query<(&Position, &mut Transform), (With<Tile>, Changed<Position>)>,
-    window: Query<&Window, With<PrimaryWindow>>,
     board: Res<Board>,
     tiling: Res<Tiling>,
 ) {
-    let Ok(primary) = window.get_single() else {
-        error!("No window exists!");
-        return;
-    };
-
     for (pos, mut transform) in &mut tiles {
-        transform.translation = pos.to_translation(&tiling, &board, primary)
+        transform.translation = pos.to_translation(&tiling, &board)
     }
 }
 
 fn resize_tiles(
-    mut tiles: Query<&mut Transform, With<Tile>>,
+    mut tiles: Query<(&Position, &mut Transform), With<Tile>>,
     mut resize: EventReader<WindowResized>,
     mut tiling: ResMut<Tiling>,
     handles: Res<TileHandles>,
@@ -268,8 +270,9 @@ fn resize_tiles(
         window_dims.height,
     );
 
-    for mut transform in &mut tiles {
-        transform.scale = Vec3::new(tiling.horizontal_scale, tiling.vertical_scale, 0.0);
+    for (pos, mut transform) in &mut tiles {
+        transform.scale = tiling.scale();
+        transform.translation = pos.to_translation(&tiling, &board);
     }
 }
 
@@ -283,8 +286,16 @@ pub fn resize_tiling(
     //retrieving tile images dimensions
     let dims = tile_descriptor.size;
 
-    let horizontal_scale = win_width / (board.columns as u32 * dims.width) as f32;
-    let vertical_scale = win_height / (board.rows as u32 * dims.height) as f32;
+    //the board is laid out between the HUD and the footer, centered in the rest of the window
+    let win_height = (win_height - HUD_HEIGHT - FOOTER_HEIGHT).max(0.0);
+    tiling.vertical_offset = (HUD_HEIGHT - FOOTER_HEIGHT) / 2.0;
+
+    //tiles keep their aspect ratio, so the board fits the most constrained dimension of the window
+    let scale = f32::min(
+        win_width / (board.columns as u32 * dims.width) as f32,
+        win_height / (board.rows as u32 * dims.height) as f32,
+    );
+    let (horizontal_scale, vertical_scale) = (scale, scale);
 
     //update the relevant fields
     tiling.width = dims.width as f32 * horizontal_scale;
@@ -299,42 +310,27 @@ pub struct TilingPlugin;
 impl Plugin for TilingPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<SpawnTile>()
+            .add_event::<TileSpawned>()
             .init_resource::<Board>()
             .init_resource::<Tiling>()
+            .init_resource::<GameState>()
+            .init_resource::<SpawnRules>()
             .add_systems(
                 Update,
-                (resize_tiles.before(spawn_tile), move_tiles, update_value)
-                    .run_if(in_state(AppState::InGame)),
+                (
+                    //the window can also be resized from the settings, while the game is paused
+                    resize_tiles
+                        .before(spawn_tile)
+                        .run_if(not(in_state(AppState::Loading))),
+                    (move_tiles, update_value).run_if(in_state(AppState::InGame)),
+                ),
             )
             .add_systems(PostUpdate, spawn_tile);
     }
 }
 
-pub fn detect_stale_board(
-    query: Query<(&Position, &TileKind), With<Tile>>,
-    board: Res<Board>,
-    mut game_over: EventWriter<FinishGame>,
-) {
-    let mut tiles: Vec<_> = query.iter().collect();
-
-    //if we're in a state where no space is left
-    if tiles.len() == board.rows * board.columns {
-        tiles.sort_by(|(pos1, _), (pos2, _)| pos1.cmp(pos2));
-        for (i, (_, kind)) in tiles.iter().enumerate() {
-            //checking if right neighbour has the same value
-            if let Some((_, val)) = tiles.get(i + 1) {
-                //if this tile is the last of the row then we don't have a right neighbour, and are instead looking
-                //at the first tile of the next row
-                if val == kind && i < board.columns {
-                    return;
-                }
-            //checking if the down neighbour has the same value
-            } else if let Some((_, val)) = tiles.get(i + board.columns) {
-                if val == kind {
-                    return;
-                }
-            }
-        }
+pub fn detect_stale_board(game: Res<GameState>, mut game_over: EventWriter<FinishGame>) {
+    if game.is_over() {
         game_over.send(FinishGame::GameOver)
     }
 }
@@ -0,0 +1,106 @@
+use super::format_date;
+use crate::*;
+use bevy::prelude::*;
+
+#[derive(Debug, Component)]
+pub struct AchievementsMenu;
+
+impl Menu for AchievementsMenu {}
+
+/// Opens the [`AchievementsMenu`] from the main menu
+#[derive(Debug, Component)]
+pub struct AchievementsButton;
+
+#[derive(Debug, Component)]
+pub struct CloseAchievementsButton;
+
+pub fn spawn_achievements_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    unlocked: Res<UnlockedAchievements>,
+) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let count = ACHIEVEMENTS
+        .iter()
+        .filter(|achievement| unlocked.is_unlocked(achievement))
+        .count();
+    commands
+        .spawn((AchievementsMenu, default_menu_backdrop()))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Achievements {count}/{}", ACHIEVEMENTS.len()),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 72.0,
+                    color: Color::BLACK,
+                },
+            ));
+
+            //locked achievements are greyed out, but their description tells how to unlock them
+            for achievement in ACHIEVEMENTS {
+                let (name, background) = match unlocked.get(achievement) {
+                    Some(date) => (
+                        format!("🏆 {} ({})\n", achievement.name, format_date(date)),
+                        Color::hex("edc22e").unwrap(),
+                    ),
+                    None => (
+                        format!("{}\n", achievement.name),
+                        Color::hex("cdc1b4").unwrap(),
+                    ),
+                };
+                parent.spawn(TextBundle {
+                    text: Text::from_sections([
+                        TextSection::new(
+                            name,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        TextSection::new(
+                            achievement.description,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::hex("776e65").unwrap(),
+                            },
+                        ),
+                    ]),
+                    style: Style {
+                        width: Val::Px(600.0),
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: background.into(),
+                    ..default()
+                });
+            }
+
+            spawn_button(
+                parent,
+                &asset_server,
+                CloseAchievementsButton,
+                "Back",
+                Color::hex("776e65").unwrap(),
+            );
+        });
+}
+
+pub fn open_achievements(
+    query: Query<&Interaction, (Changed<Interaction>, With<AchievementsButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::AchievementsMenu)
+    }
+}
+
+pub fn close_achievements(
+    query: Query<&Interaction, (Changed<Interaction>, With<CloseAchievementsButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::MainMenu)
+    }
+}
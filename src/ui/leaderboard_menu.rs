@@ -0,0 +1,263 @@
+use crate::*;
+use bevy::prelude::*;
+
+#[derive(Debug, Component)]
+pub struct LeaderboardMenu;
+
+impl Menu for LeaderboardMenu {}
+
+/// Opens the [`LeaderboardMenu`] from the main menu
+#[derive(Debug, Component)]
+pub struct LeaderboardButton;
+
+#[derive(Debug, Component)]
+pub struct CloseLeaderboardButton;
+
+/// Cycles through the rules whose games are shown
+#[derive(Debug, Component)]
+pub struct ModeFilterButton;
+
+/// Cycles through the boards whose games are shown
+#[derive(Debug, Component)]
+pub struct SizeFilterButton;
+
+#[derive(Debug, Component)]
+pub struct LeaderboardTable;
+
+/// Games shown in the leaderboard, kept while the game runs so that the menu opens as it was left
+#[derive(Debug, Resource, Clone, Default, PartialEq, Eq)]
+pub struct LeaderboardFilter {
+    /// Name of the rules, all of them if `None`
+    mode: Option<&'static str>,
+    /// All boards if `None`
+    board: Option<Board>,
+}
+
+impl LeaderboardFilter {
+    /// Modes which can be filtered, the [`SpawnRules::presets`] and any other rules
+    fn modes() -> Vec<&'static str> {
+        let mut modes: Vec<_> = SpawnRules::presets()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        modes.push("Custom");
+        modes
+    }
+
+    fn matches(&self, entry: &LeaderboardEntry) -> bool {
+        self.mode.is_none_or(|mode| entry.mode() == mode)
+            && self
+                .board
+                .as_ref()
+                .is_none_or(|board| entry.board == *board)
+    }
+
+    fn mode_label(&self) -> String {
+        format!("Mode: {}", self.mode.unwrap_or("All"))
+    }
+
+    fn size_label(&self) -> String {
+        match &self.board {
+            Some(board) => format!("Size: {}x{}", board.columns, board.rows),
+            None => "Size: All".to_string(),
+        }
+    }
+}
+
+/// Item following `current` in `items`, `None` standing for all of them before the first one
+fn cycle<T: Clone + PartialEq>(items: &[T], current: &Option<T>) -> Option<T> {
+    let next = match current {
+        Some(current) => items
+            .iter()
+            .position(|item| item == current)
+            .map(|idx| idx + 1),
+        None => Some(0),
+    };
+    next.and_then(|idx| items.get(idx)).cloned()
+}
+
+pub fn spawn_leaderboard_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    filter: Res<LeaderboardFilter>,
+) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    commands
+        .spawn((LeaderboardMenu, default_menu_backdrop()))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "High Scores",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 90.0,
+                    color: Color::BLACK,
+                },
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(16.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(
+                        parent,
+                        &asset_server,
+                        ModeFilterButton,
+                        filter.mode_label(),
+                        Color::hex("bbada0").unwrap(),
+                    )
+                    .insert(FILTER_BUTTON_STYLE);
+                    spawn_button(
+                        parent,
+                        &asset_server,
+                        SizeFilterButton,
+                        filter.size_label(),
+                        Color::hex("bbada0").unwrap(),
+                    )
+                    .insert(FILTER_BUTTON_STYLE);
+                });
+
+            parent.spawn((
+                LeaderboardTable,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font,
+                        font_size: 18.0,
+                        color: Color::BLACK,
+                    },
+                ),
+            ));
+
+            spawn_button(
+                parent,
+                &asset_server,
+                CloseLeaderboardButton,
+                "Back",
+                Color::hex("776e65").unwrap(),
+            );
+        });
+}
+
+const FILTER_BUTTON_STYLE: Style = {
+    let mut style = DEFAULT_BUTTON_STYLE;
+    style.width = Val::Px(320.0);
+    style
+};
+
+pub fn open_leaderboard(
+    query: Query<&Interaction, (Changed<Interaction>, With<LeaderboardButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::LeaderboardMenu)
+    }
+}
+
+pub fn close_leaderboard(
+    query: Query<&Interaction, (Changed<Interaction>, With<CloseLeaderboardButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::MainMenu)
+    }
+}
+
+pub fn cycle_leaderboard_filters(
+    mode_button: Query<(&Interaction, &Children), (Changed<Interaction>, With<ModeFilterButton>)>,
+    size_button: Query<(&Interaction, &Children), (Changed<Interaction>, With<SizeFilterButton>)>,
+    mut texts: Query<&mut Text>,
+    mut filter: ResMut<LeaderboardFilter>,
+    leaderboard: Res<Leaderboard>,
+) {
+    if let Ok((Interaction::Pressed, children)) = mode_button.get_single() {
+        filter.mode = cycle(&LeaderboardFilter::modes(), &filter.mode);
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value = filter.mode_label();
+            }
+        }
+    }
+    if let Ok((Interaction::Pressed, children)) = size_button.get_single() {
+        //only the boards games were played on are worth filtering
+        let mut boards: Vec<_> = leaderboard
+            .entries()
+            .iter()
+            .map(|entry| entry.board.clone())
+            .collect();
+        boards.sort_by_key(|board| (board.columns, board.rows));
+        boards.dedup();
+        filter.board = cycle(&boards, &filter.board);
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value = filter.size_label();
+            }
+        }
+    }
+}
+
+/// Number of games listed at once
+const SHOWN: usize = 10;
+
+pub fn update_leaderboard_table(
+    mut table: Query<(Ref<LeaderboardTable>, &mut Text)>,
+    filter: Res<LeaderboardFilter>,
+    leaderboard: Res<Leaderboard>,
+) {
+    let Ok((marker, mut text)) = table.get_single_mut() else {
+        return;
+    };
+    if !marker.is_added() && !filter.is_changed() && !leaderboard.is_changed() {
+        return;
+    }
+
+    let mut lines = vec![format!(
+        "{:>2} {:>7} {:>5} {:>5} {:>6} {:<10} {:<4} {:<7} {}",
+        "#", "Score", "Tile", "Moves", "Time", "Date", "Size", "Mode", "Seed"
+    )];
+    let entries = leaderboard
+        .entries()
+        .iter()
+        .filter(|entry| filter.matches(entry))
+        .take(SHOWN);
+    for (rank, entry) in entries.enumerate() {
+        let seconds = entry.duration.as_secs();
+        lines.push(format!(
+            "{:>2} {:>7} {:>5} {:>5} {:>6} {:<10} {:<4} {:<7} {}",
+            rank + 1,
+            entry.score,
+            entry.largest_tile,
+            entry.moves,
+            format!("{}:{:02}", seconds / 60, seconds % 60),
+            format_date(entry.date),
+            format!("{}x{}", entry.board.columns, entry.board.rows),
+            entry.mode(),
+            entry.seed
+        ));
+    }
+    if lines.len() == 1 {
+        lines.push("No game was finished yet".to_string());
+    }
+    text.sections[0].value = lines.join("\n");
+}
+
+/// Formats a Unix timestamp as a UTC date such as 2024-01-31
+pub(crate) fn format_date(timestamp: u64) -> String {
+    //days to civil date conversion from Howard Hinnant's date algorithms, days are counted from 0000-03-01 so that
+    //leap days fall at the end of the year
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year}-{month:02}-{day:02}")
+}
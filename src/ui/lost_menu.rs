@@ -0,0 +1,110 @@
+use crate::*;
+use bevy::prelude::*;
+
+#[derive(Debug, Component)]
+pub struct LostMenu;
+
+impl Menu for LostMenu {}
+
+/// Starts a new game on the same board with a random seed
+#[derive(Debug, Component)]
+pub struct RetryButton;
+
+/// Starts the game that was just lost over
+#[derive(Debug, Component)]
+pub struct RetrySeedButton;
+
+pub fn spawn_lost_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    game: Res<GameState>,
+    new_best: Res<NewPersonalBest>,
+) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    commands
+        .spawn((LostMenu, default_menu_backdrop()))
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text {
+                    sections: vec![
+                        TextSection::new(
+                            "Game Over\n",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 90.0,
+                                color: Color::hex("776e65").unwrap(),
+                            },
+                        ),
+                        TextSection::new(
+                            if new_best.0 {
+                                "New personal best!\n"
+                            } else {
+                                ""
+                            },
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 48.0,
+                                color: Color::GOLD,
+                            },
+                        ),
+                        TextSection::new(
+                            format!(
+                                "Score: {}\nLargest tile: {}\nMoves: {}\nSeed: {}",
+                                score.0,
+                                2u64.pow(game.highest_power() as u32),
+                                game.moves(),
+                                game.seed()
+                            ),
+                            TextStyle {
+                                font,
+                                font_size: 40.0,
+                                color: Color::BLACK,
+                            },
+                        ),
+                    ],
+                    alignment: TextAlignment::Center,
+                    ..default()
+                },
+                ..default()
+            });
+
+            spawn_button(
+                parent,
+                &asset_server,
+                RetryButton,
+                "Retry",
+                Color::hex("edc22e").unwrap(),
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                RetrySeedButton,
+                "Same seed",
+                Color::hex("8f7a66").unwrap(),
+            );
+            BackToMenuButton::spawn(parent, &asset_server);
+            ExitButton::spawn(parent, &asset_server);
+        });
+}
+
+pub fn retry(
+    retry: Query<&Interaction, (Changed<Interaction>, With<RetryButton>)>,
+    retry_seed: Query<&Interaction, (Changed<Interaction>, With<RetrySeedButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut seed: ResMut<GameSeed>,
+    mut rules: ResMut<SpawnRules>,
+    game: Res<GameState>,
+) {
+    if let Ok(Interaction::Pressed) = retry.get_single() {
+        seed.0 = None;
+    } else if let Ok(Interaction::Pressed) = retry_seed.get_single() {
+        //the same seed only gives the same game with the same rules
+        seed.0 = Some(game.seed());
+        *rules = game.rules().clone();
+    } else {
+        return;
+    }
+    //the board is still the one of the lost game
+    app_state.set(AppState::Setup)
+}
@@ -1,3 +1,4 @@
+use super::{AchievementsButton, LeaderboardButton, SettingsButton, StatsButton};
 use crate::*;
 use bevy::prelude::*;
 
@@ -7,7 +8,13 @@ pub struct MainMenu;
 impl Menu for MainMenu {}
 
 #[derive(Debug, Component)]
-pub struct StartButton;
+pub struct NewGameButton;
+
+#[derive(Debug, Component)]
+pub struct ContinueButton;
+
+#[derive(Debug, Component)]
+pub struct WatchReplayButton;
 
 const TRANSPARENT: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
 
@@ -15,6 +22,7 @@ pub fn spawn_main_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     tiles: Res<TileHandles>,
+    replay_path: Res<ReplayPath>,
 ) {
     commands
         .spawn((MainMenu, default_menu_backdrop()))
@@ -44,44 +52,123 @@ pub fn spawn_main_menu(
                         });
                     });
                 });
-            //start game button
+            //continue the saved game, if any
+            if SavedGame::exists() {
+                spawn_button(
+                    parent,
+                    &asset_server,
+                    ContinueButton,
+                    "Continue",
+                    Color::hex("edc22e").unwrap(),
+                );
+            }
+
+            //choose the board and start a new game
+            spawn_button(
+                parent,
+                &asset_server,
+                NewGameButton,
+                "Play",
+                Color::hex("776e65").unwrap(),
+            );
+
+            //watch the replay given on the command line or choose one of the last games played
+            if replay_path.0.is_some() || Replay::any_stored() {
+                spawn_button(
+                    parent,
+                    &asset_server,
+                    WatchReplayButton,
+                    "Replay",
+                    Color::hex("8f7a66").unwrap(),
+                );
+            }
+
+            //best games played, statistics of all of them and achievements, side by side to save room
             parent
-                .spawn((
-                    StartButton,
-                    ButtonBundle {
-                        background_color: Color::hex("776e65").unwrap().into(),
-                        style: DEFAULT_BUTTON_STYLE,
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(8.0),
                         ..default()
                     },
-                ))
+                    ..default()
+                })
                 .with_children(|parent| {
-                    parent.spawn(TextBundle {
-                        text: Text {
-                            sections: vec![TextSection::new(
-                                "Play",
-                                TextStyle {
-                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
-                                    font_size: 32.0,
-                                    color: Color::WHITE,
-                                },
-                            )],
-                            alignment: TextAlignment::Center,
-                            ..default()
-                        },
-                        ..default()
-                    });
+                    spawn_button(
+                        parent,
+                        &asset_server,
+                        LeaderboardButton,
+                        "Scores",
+                        Color::hex("f59563").unwrap(),
+                    );
+                    spawn_button(
+                        parent,
+                        &asset_server,
+                        StatsButton,
+                        "Stats",
+                        Color::hex("f67c5f").unwrap(),
+                    );
+                    spawn_button(
+                        parent,
+                        &asset_server,
+                        AchievementsButton,
+                        "Trophies",
+                        Color::hex("edcc61").unwrap(),
+                    );
                 });
 
+            //key bindings and preferences
+            spawn_button(
+                parent,
+                &asset_server,
+                SettingsButton,
+                "Settings",
+                Color::hex("bbada0").unwrap(),
+            );
+
             //exit button
             ExitButton::spawn(parent, &asset_server);
         });
 }
 
-pub fn start_game(
-    query: Query<&Interaction, (Changed<Interaction>, With<StartButton>)>,
+pub fn open_new_game_menu(
+    query: Query<&Interaction, (Changed<Interaction>, With<NewGameButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::NewGameMenu)
+    }
+}
+
+pub fn continue_game(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<ContinueButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        match SavedGame::load() {
+            Some(saved) => commands.insert_resource(ResumeGame(saved)),
+            None => warn!("The saved game could not be loaded, starting a new one"),
+        }
+        app_state.set(AppState::Setup)
+    }
+}
+
+pub fn watch_replay(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<WatchReplayButton>)>,
     mut app_state: ResMut<NextState<AppState>>,
+    replay_path: Res<ReplayPath>,
 ) {
     if let Ok(Interaction::Pressed) = query.get_single() {
+        if replay_path.0.is_none() {
+            app_state.set(AppState::ReplaysMenu);
+            return;
+        }
+        let Some(replay) = replay_path.load() else {
+            warn!("No replay could be loaded");
+            return;
+        };
+        commands.insert_resource(Playback::new(replay));
         app_state.set(AppState::Setup)
     }
 }
@@ -1,16 +1,30 @@
 //todo make it so text in menus have a dynamic font size based on screen size
 
 use crate::*;
-use bevy::{app::AppExit, prelude::*};
+use bevy::{app::AppExit, ecs::system::EntityCommands, prelude::*};
 
+mod achievements_menu;
 mod congrats;
+mod leaderboard_menu;
+mod lost_menu;
 mod main_menu;
+mod new_game_menu;
 mod pause_menu;
+mod replays_menu;
+mod settings_menu;
+mod stats_menu;
 mod won_menu;
 
+use achievements_menu::*;
 use congrats::*;
+use leaderboard_menu::*;
+use lost_menu::*;
 use main_menu::*;
+use new_game_menu::*;
 use pause_menu::*;
+use replays_menu::*;
+use settings_menu::*;
+use stats_menu::*;
 use won_menu::*;
 
 #[derive(Debug)]
@@ -19,28 +33,85 @@ pub struct GameInterfacePlugin;
 impl Plugin for GameInterfacePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<TogglePause>()
-            .add_state::<Congratulation>()
+            .init_resource::<LeaderboardFilter>()
             .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
             .add_systems(OnExit(AppState::MainMenu), despawn_menu::<MainMenu>)
+            .add_systems(OnEnter(AppState::NewGameMenu), spawn_new_game_menu)
+            .add_systems(OnExit(AppState::NewGameMenu), despawn_menu::<NewGameMenu>)
             .add_systems(OnEnter(AppState::Paused), spawn_pause_menu)
             .add_systems(OnExit(AppState::Paused), despawn_menu::<PauseMenu>)
             .add_systems(OnEnter(AppState::WonMenu), spawn_won_menu)
             .add_systems(OnExit(AppState::WonMenu), despawn_menu::<WonMenu>)
+            .add_systems(OnEnter(AppState::LostMenu), spawn_lost_menu)
+            .add_systems(OnExit(AppState::LostMenu), despawn_menu::<LostMenu>)
+            .add_systems(OnEnter(AppState::SettingsMenu), spawn_settings_menu)
+            .add_systems(OnExit(AppState::SettingsMenu), despawn_menu::<SettingsMenu>)
             .add_systems(OnEnter(AppState::CongratsMenu), spawn_congrats_menu)
             .add_systems(OnExit(AppState::CongratsMenu), despawn_menu::<CongratsMenu>)
+            .add_systems(OnEnter(AppState::LeaderboardMenu), spawn_leaderboard_menu)
+            .add_systems(
+                OnExit(AppState::LeaderboardMenu),
+                despawn_menu::<LeaderboardMenu>,
+            )
+            .add_systems(OnEnter(AppState::StatsMenu), spawn_stats_menu)
+            .add_systems(OnExit(AppState::StatsMenu), despawn_menu::<StatsMenu>)
+            .add_systems(OnEnter(AppState::AchievementsMenu), spawn_achievements_menu)
+            .add_systems(
+                OnExit(AppState::AchievementsMenu),
+                despawn_menu::<AchievementsMenu>,
+            )
+            .add_systems(OnEnter(AppState::ReplaysMenu), spawn_replays_menu)
+            .add_systems(OnExit(AppState::ReplaysMenu), despawn_menu::<ReplaysMenu>)
             .add_systems(
                 Update,
                 (
-                    start_game,
+                    open_new_game_menu,
+                    continue_game,
+                    (
+                        watch_replay,
+                        (open_replay, close_replays).run_if(in_state(AppState::ReplaysMenu)),
+                    ),
+                    (
+                        start_game,
+                        cancel_new_game,
+                        resize_board,
+                        cycle_rules,
+                        cycle_goal,
+                        cycle_undo_limit,
+                        update_board_labels.run_if(resource_changed::<Board>()),
+                        edit_seed,
+                    )
+                        .run_if(in_state(AppState::NewGameMenu)),
+                    (open_leaderboard, open_stats, open_achievements)
+                        .run_if(in_state(AppState::MainMenu)),
+                    (
+                        close_leaderboard,
+                        (cycle_leaderboard_filters, update_leaderboard_table).chain(),
+                    )
+                        .run_if(in_state(AppState::LeaderboardMenu)),
+                    close_stats.run_if(in_state(AppState::StatsMenu)),
+                    close_achievements.run_if(in_state(AppState::AchievementsMenu)),
+                    open_settings,
+                    (
+                        close_settings,
+                        (rebind_key, cycle_keybind_preset, update_keybind_labels).chain(),
+                        step_setting,
+                        cycle_setting,
+                        update_setting_labels.run_if(resource_changed::<Preferences>()),
+                    )
+                        .run_if(in_state(AppState::SettingsMenu)),
                     exit_app,
                     toggle_pause,
                     pause_with_keybind,
                     resume_game,
+                    toggle_ai.run_if(in_state(AppState::Paused)),
                     back_to_menu,
                     trigger_congrats_menu
-                        .after(score_from_merge)
-                        .run_if(in_state(Congratulation::NotYet)),
+                        .after(detect_milestones)
+                        .run_if(in_state(AppState::InGame)),
                     return_to_game.run_if(in_state(AppState::CongratsMenu)),
+                    retry.run_if(in_state(AppState::LostMenu)),
+                    apply_theme,
                 ),
             );
     }
@@ -53,35 +124,52 @@ pub(crate) struct ExitButton;
 
 impl ExitButton {
     pub(crate) fn spawn(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>) {
-        parent
-            .spawn((
-                ExitButton,
-                ButtonBundle {
-                    background_color: Color::hex("f65e3b").unwrap().into(),
-                    style: DEFAULT_BUTTON_STYLE,
-                    ..default()
-                },
-            ))
-            .with_children(|parent| {
-                parent.spawn(TextBundle {
-                    text: Text {
-                        sections: vec![TextSection::new(
-                            "Exit",
-                            TextStyle {
-                                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
-                                font_size: 32.0,
-                                color: Color::WHITE,
-                            },
-                        )],
-                        alignment: TextAlignment::Center,
-                        ..default()
-                    },
-                    ..default()
-                });
-            });
+        spawn_button(
+            parent,
+            asset_server,
+            ExitButton,
+            "Exit",
+            Color::hex("f65e3b").unwrap(),
+        );
     }
 }
 
+/// Spawns a button of the default style labelled with `label`, `marker` is used to find out when it's pressed
+pub(crate) fn spawn_button<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    asset_server: &Res<AssetServer>,
+    marker: impl Bundle,
+    label: impl Into<String>,
+    color: Color,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut button = parent.spawn((
+        marker,
+        ButtonBundle {
+            background_color: color.into(),
+            style: DEFAULT_BUTTON_STYLE,
+            ..default()
+        },
+    ));
+    button.with_children(|parent| {
+        parent.spawn(TextBundle {
+            text: Text {
+                sections: vec![TextSection::new(
+                    label,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                        font_size: 32.0,
+                        color: Color::WHITE,
+                    },
+                )],
+                alignment: TextAlignment::Center,
+                ..default()
+            },
+            ..default()
+        });
+    });
+    button
+}
+
 pub(crate) const DEFAULT_BUTTON_STYLE: Style = {
     let mut style = Style::DEFAULT;
     style.width = Val::Px(200.0);
@@ -115,7 +203,7 @@ fn pause_with_keybind(
     keybinds: Res<Keybinds>,
     mut toggle_pause: EventWriter<TogglePause>,
 ) {
-    if keys.just_pressed(keybinds.pause_game) {
+    if keybinds.just_pressed(Action::PauseGame, &keys) {
         toggle_pause.send(TogglePause);
     }
 }
@@ -145,20 +233,62 @@ fn toggle_pause(
     toggle_pause.clear();
 }
 
-pub(crate) fn default_menu_backdrop() -> NodeBundle {
-    NodeBundle {
-        background_color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
-        style: Style {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            flex_direction: FlexDirection::Column,
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            row_gap: Val::Px(8.0),
-            column_gap: Val::Px(8.0),
+/// Marks the root of every menu, so that it follows the [`Theme`]
+#[derive(Debug, Component)]
+pub(crate) struct Backdrop;
+
+pub(crate) fn default_menu_backdrop() -> (Backdrop, NodeBundle) {
+    (
+        Backdrop,
+        NodeBundle {
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                column_gap: Val::Px(8.0),
+                ..default()
+            },
             ..default()
         },
-        ..default()
+    )
+}
+
+/// Colors the background and the menus after the [`Theme`] chosen in the settings
+///
+/// Only text written in a theme's text color follows it, the colored titles and the buttons' labels are left as is.
+fn apply_theme(
+    preferences: Res<Preferences>,
+    mut clear_color: ResMut<ClearColor>,
+    mut backdrops: Query<(Entity, Ref<Backdrop>, &mut BackgroundColor)>,
+    mut texts: Query<&mut Text>,
+    children: Query<&Children>,
+) {
+    let theme = preferences.theme;
+    if preferences.is_changed() {
+        clear_color.0 = theme.background();
+    }
+    for (menu, backdrop, mut background) in &mut backdrops {
+        if !preferences.is_changed() && !backdrop.is_added() {
+            continue;
+        }
+        background.0 = theme.backdrop();
+        for entity in children.iter_descendants(menu) {
+            let Ok(mut text) = texts.get_mut(entity) else {
+                continue;
+            };
+            for section in &mut text.sections {
+                if Theme::ALL
+                    .iter()
+                    .any(|other| other.text() == section.style.color)
+                {
+                    section.style.color = theme.text();
+                }
+            }
+        }
     }
 }
 
@@ -176,31 +306,12 @@ pub fn back_to_menu(
 
 impl BackToMenuButton {
     pub(crate) fn spawn(parent: &mut ChildBuilder, asset_server: &Res<AssetServer>) {
-        parent
-            .spawn((
-                BackToMenuButton,
-                ButtonBundle {
-                    style: DEFAULT_BUTTON_STYLE,
-                    background_color: Color::hex("776e65").unwrap().into(),
-                    ..default()
-                },
-            ))
-            .with_children(|parent| {
-                parent.spawn(TextBundle {
-                    text: Text {
-                        sections: vec![TextSection::new(
-                            "Main Menu",
-                            TextStyle {
-                                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
-                                font_size: 32.0,
-                                color: Color::WHITE,
-                            },
-                        )],
-                        alignment: TextAlignment::Center,
-                        ..default()
-                    },
-                    ..default()
-                });
-            });
+        spawn_button(
+            parent,
+            asset_server,
+            BackToMenuButton,
+            "Main Menu",
+            Color::hex("776e65").unwrap(),
+        );
     }
 }
@@ -0,0 +1,442 @@
+use crate::*;
+use bevy::prelude::*;
+
+/// Smallest number of rows or columns a board can have
+pub const MIN_BOARD_SIDE: usize = 3;
+/// Largest number of rows or columns a board can have
+pub const MAX_BOARD_SIDE: usize = 8;
+
+#[derive(Debug, Component)]
+pub struct NewGameMenu;
+
+impl Menu for NewGameMenu {}
+
+#[derive(Debug, Component)]
+pub struct StartButton;
+
+#[derive(Debug, Component)]
+pub struct CancelButton;
+
+/// Cycles through the [`SpawnRules::presets`]
+#[derive(Debug, Component)]
+pub struct RulesButton;
+
+impl RulesButton {
+    fn label(rules: &SpawnRules) -> String {
+        format!("Rules: {}", rules.preset_name().unwrap_or("Custom"))
+    }
+}
+
+/// Cycles through the tiles that can be set as the [`WinTarget`]
+#[derive(Debug, Component)]
+pub struct GoalButton;
+
+impl GoalButton {
+    /// Powers of the tiles which can be chosen as targets, from 512 to 8192
+    const POWERS: std::ops::RangeInclusive<u32> = 9..=13;
+
+    fn label(target: &WinTarget) -> String {
+        format!("Goal: {}", 2u32.pow(target.power))
+    }
+}
+
+/// Cycles through the [`UndoRules`] a game can be played with
+#[derive(Debug, Component)]
+pub struct UndoButton;
+
+impl UndoButton {
+    /// Undo limits which can be chosen, `None` meaning unlimited
+    const LIMITS: [Option<u32>; 5] = [None, Some(0), Some(1), Some(3), Some(5)];
+
+    fn label(rules: &UndoRules) -> String {
+        match rules.limit {
+            None => "Undos: unlimited".to_string(),
+            Some(0) => "Undos: none".to_string(),
+            Some(limit) => format!("Undos: {limit}"),
+        }
+    }
+}
+
+/// Adds `delta` rows or columns to the board
+#[derive(Debug, Component)]
+pub struct ResizeButton {
+    dimension: BoardLabel,
+    delta: isize,
+}
+
+impl ResizeButton {
+    fn spawn(parent: &mut ChildBuilder, font: &Handle<Font>, dimension: BoardLabel, delta: isize) {
+        parent
+            .spawn((
+                ResizeButton { dimension, delta },
+                ButtonBundle {
+                    background_color: Color::hex("bbada0").unwrap().into(),
+                    style: Style {
+                        width: Val::Px(64.0),
+                        height: Val::Px(64.0),
+                        ..DEFAULT_BUTTON_STYLE
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    if delta < 0 { "-" } else { "+" },
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            });
+    }
+}
+
+/// Text describing the board being set up
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq)]
+pub enum BoardLabel {
+    Columns,
+    Rows,
+    BestScore,
+}
+
+impl BoardLabel {
+    fn text(&self, board: &Board, best_scores: &BestScores) -> String {
+        match self {
+            BoardLabel::Columns => format!("Width: {}", board.columns),
+            BoardLabel::Rows => format!("Height: {}", board.rows),
+            BoardLabel::BestScore => format!("Best score: {}", best_scores.get(board)),
+        }
+    }
+}
+
+/// Field in which the seed of the next game can be typed
+#[derive(Debug, Component, Default)]
+pub struct SeedInput {
+    editing: bool,
+    digits: String,
+}
+
+impl SeedInput {
+    fn label(&self) -> String {
+        match (self.editing, self.digits.is_empty()) {
+            (true, _) => format!("Seed: {}_", self.digits),
+            (false, true) => "Seed: random".to_string(),
+            (false, false) => format!("Seed: {}", self.digits),
+        }
+    }
+}
+
+pub fn spawn_new_game_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut board: ResMut<Board>,
+    preferences: Res<Preferences>,
+    best_scores: Res<BestScores>,
+    seed: Res<GameSeed>,
+    rules: Res<SpawnRules>,
+    target: Res<WinTarget>,
+    undo_rules: Res<UndoRules>,
+) {
+    //new games start on the board chosen in the settings
+    *board = preferences.board.clone();
+
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let label = |label: BoardLabel| {
+        (
+            label,
+            TextBundle::from_section(
+                label.text(&board, &best_scores),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    color: Color::BLACK,
+                },
+            ),
+        )
+    };
+    let seed_input = SeedInput {
+        editing: false,
+        digits: seed.0.map(|seed| seed.to_string()).unwrap_or_default(),
+    };
+
+    commands
+        .spawn((NewGameMenu, default_menu_backdrop()))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "New Game",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 90.0,
+                    color: Color::BLACK,
+                },
+            ));
+
+            //a line per dimension of the board, with buttons to decrease and increase it
+            for dimension in [BoardLabel::Columns, BoardLabel::Rows] {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(16.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        ResizeButton::spawn(parent, &font, dimension, -1);
+                        parent.spawn(label(dimension));
+                        ResizeButton::spawn(parent, &font, dimension, 1);
+                    });
+            }
+
+            parent.spawn(label(BoardLabel::BestScore));
+
+            //how tiles spawn
+            spawn_button(
+                parent,
+                &asset_server,
+                RulesButton,
+                RulesButton::label(&rules),
+                Color::hex("bbada0").unwrap(),
+            )
+            .insert(Style {
+                width: Val::Px(416.0),
+                ..DEFAULT_BUTTON_STYLE
+            });
+
+            //tile to reach
+            spawn_button(
+                parent,
+                &asset_server,
+                GoalButton,
+                GoalButton::label(&target),
+                Color::hex("bbada0").unwrap(),
+            )
+            .insert(Style {
+                width: Val::Px(416.0),
+                ..DEFAULT_BUTTON_STYLE
+            });
+
+            //how many moves can be taken back
+            spawn_button(
+                parent,
+                &asset_server,
+                UndoButton,
+                UndoButton::label(&undo_rules),
+                Color::hex("bbada0").unwrap(),
+            )
+            .insert(Style {
+                width: Val::Px(416.0),
+                ..DEFAULT_BUTTON_STYLE
+            });
+
+            //seed of the next game
+            let text = seed_input.label();
+            parent
+                .spawn((
+                    seed_input,
+                    ButtonBundle {
+                        background_color: Color::hex("bbada0").unwrap().into(),
+                        style: Style {
+                            width: Val::Px(416.0),
+                            ..DEFAULT_BUTTON_STYLE
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        text,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+
+            spawn_button(
+                parent,
+                &asset_server,
+                StartButton,
+                "Start",
+                Color::hex("776e65").unwrap(),
+            );
+            spawn_button(
+                parent,
+                &asset_server,
+                CancelButton,
+                "Back",
+                Color::hex("8f7a66").unwrap(),
+            );
+        });
+}
+
+pub fn start_game(
+    query: Query<&Interaction, (Changed<Interaction>, With<StartButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+    rules: Res<SpawnRules>,
+    board: Res<Board>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        match rules.validate(&board) {
+            Ok(()) => app_state.set(AppState::Setup),
+            Err(err) => warn!("Can't start a game with these rules: {err}"),
+        }
+    }
+}
+
+pub fn cancel_new_game(
+    query: Query<&Interaction, (Changed<Interaction>, With<CancelButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::MainMenu)
+    }
+}
+
+pub fn resize_board(
+    query: Query<(&Interaction, &ResizeButton), Changed<Interaction>>,
+    mut board: ResMut<Board>,
+) {
+    for (interaction, button) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let side = match button.dimension {
+            BoardLabel::Columns => &mut board.columns,
+            BoardLabel::Rows => &mut board.rows,
+            BoardLabel::BestScore => continue,
+        };
+        *side = side
+            .saturating_add_signed(button.delta)
+            .clamp(MIN_BOARD_SIDE, MAX_BOARD_SIDE);
+    }
+}
+
+pub fn cycle_rules(
+    query: Query<(&Interaction, &Children), (Changed<Interaction>, With<RulesButton>)>,
+    mut texts: Query<&mut Text>,
+    mut rules: ResMut<SpawnRules>,
+) {
+    let Ok((Interaction::Pressed, children)) = query.get_single() else {
+        return;
+    };
+    let presets = SpawnRules::presets();
+    //custom rules are followed by the first preset
+    let next = presets
+        .iter()
+        .position(|(_, preset)| *preset == *rules)
+        .map_or(0, |current| (current + 1) % presets.len());
+    *rules = presets[next].1.clone();
+
+    for &child in children {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.sections[0].value = RulesButton::label(&rules);
+        }
+    }
+}
+
+pub fn cycle_goal(
+    query: Query<(&Interaction, &Children), (Changed<Interaction>, With<GoalButton>)>,
+    mut texts: Query<&mut Text>,
+    mut target: ResMut<WinTarget>,
+) {
+    let Ok((Interaction::Pressed, children)) = query.get_single() else {
+        return;
+    };
+    target.power = if GoalButton::POWERS.contains(&(target.power + 1)) {
+        target.power + 1
+    } else {
+        *GoalButton::POWERS.start()
+    };
+
+    for &child in children {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.sections[0].value = GoalButton::label(&target);
+        }
+    }
+}
+
+pub fn cycle_undo_limit(
+    query: Query<(&Interaction, &Children), (Changed<Interaction>, With<UndoButton>)>,
+    mut texts: Query<&mut Text>,
+    mut undo_rules: ResMut<UndoRules>,
+) {
+    let Ok((Interaction::Pressed, children)) = query.get_single() else {
+        return;
+    };
+    let limits = UndoButton::LIMITS;
+    let next = limits
+        .iter()
+        .position(|limit| *limit == undo_rules.limit)
+        .map_or(0, |current| (current + 1) % limits.len());
+    undo_rules.limit = limits[next];
+
+    for &child in children {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.sections[0].value = UndoButton::label(&undo_rules);
+        }
+    }
+}
+
+pub fn update_board_labels(
+    mut labels: Query<(&mut Text, &BoardLabel)>,
+    board: Res<Board>,
+    best_scores: Res<BestScores>,
+) {
+    for (mut text, label) in &mut labels {
+        text.sections[0].value = label.text(&board, &best_scores);
+    }
+}
+
+/// Lets the player type the seed of the next game after clicking the seed field
+pub fn edit_seed(
+    mut inputs: Query<(
+        Ref<Interaction>,
+        &mut SeedInput,
+        &mut BackgroundColor,
+        &Children,
+    )>,
+    mut texts: Query<&mut Text>,
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut seed: ResMut<GameSeed>,
+) {
+    let typed: String = characters.read().map(|event| event.char).collect();
+    let Ok((interaction, mut input, mut background, children)) = inputs.get_single_mut() else {
+        return;
+    };
+
+    if interaction.is_changed() && *interaction == Interaction::Pressed {
+        input.editing = !input.editing;
+    } else if input.editing {
+        if keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter, KeyCode::Escape]) {
+            input.editing = false;
+        } else if keys.just_pressed(KeyCode::Back) {
+            input.digits.pop();
+        } else {
+            for digit in typed.chars().filter(char::is_ascii_digit) {
+                //we refuse digits which would make the seed overflow
+                if format!("{}{digit}", input.digits).parse::<u64>().is_ok() {
+                    input.digits.push(digit);
+                }
+            }
+        }
+    } else if !input.is_changed() {
+        return;
+    }
+
+    seed.0 = input.digits.parse().ok();
+    *background = if input.editing {
+        Color::hex("8f7a66").unwrap().into()
+    } else {
+        Color::hex("bbada0").unwrap().into()
+    };
+    for &child in children {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.sections[0].value = input.label();
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::SettingsButton;
 use crate::*;
 use bevy::prelude::*;
 
@@ -9,7 +10,26 @@ impl Menu for PauseMenu {}
 #[derive(Debug, Component)]
 pub struct ResumeButton;
 
-pub fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Lets the [`AiPlayer`] play once the game is resumed, or stops it
+#[derive(Debug, Component)]
+pub struct AiToggleButton;
+
+impl AiToggleButton {
+    fn label(ai: &AiPlayer) -> &'static str {
+        if ai.enabled {
+            "AI: On"
+        } else {
+            "AI: Off"
+        }
+    }
+}
+
+pub fn spawn_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game: Res<GameState>,
+    ai: Res<AiPlayer>,
+) {
     commands
         .spawn((PauseMenu, default_menu_backdrop()))
         .with_children(|parent| {
@@ -30,6 +50,23 @@ pub fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>)
                 ..default()
             });
 
+            //seed of the current game, so it can be replayed
+            parent.spawn(TextBundle {
+                text: Text {
+                    sections: vec![TextSection::new(
+                        format!("Seed: {}", game.seed()),
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                            font_size: 32.0,
+                            color: Color::BLACK,
+                        },
+                    )],
+                    alignment: TextAlignment::Center,
+                    ..default()
+                },
+                ..default()
+            });
+
             //resume text
             parent
                 .spawn((
@@ -58,6 +95,22 @@ pub fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>)
                     });
                 });
 
+            spawn_button(
+                parent,
+                &asset_server,
+                AiToggleButton,
+                AiToggleButton::label(&ai),
+                Color::hex("8f7a66").unwrap(),
+            );
+
+            spawn_button(
+                parent,
+                &asset_server,
+                SettingsButton,
+                "Settings",
+                Color::hex("bbada0").unwrap(),
+            );
+
             //main menu
             BackToMenuButton::spawn(parent, &asset_server);
 
@@ -74,3 +127,19 @@ pub fn resume_game(
         toggle_pause.send(TogglePause)
     }
 }
+
+pub fn toggle_ai(
+    query: Query<(&Interaction, &Children), (Changed<Interaction>, With<AiToggleButton>)>,
+    mut texts: Query<&mut Text>,
+    mut ai: ResMut<AiPlayer>,
+) {
+    let Ok((Interaction::Pressed, children)) = query.get_single() else {
+        return;
+    };
+    ai.enabled = !ai.enabled;
+    for &child in children {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.sections[0].value = AiToggleButton::label(&ai).to_string();
+        }
+    }
+}
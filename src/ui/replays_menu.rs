@@ -0,0 +1,105 @@
+use super::format_date;
+use crate::*;
+use bevy::prelude::*;
+
+#[derive(Debug, Component)]
+pub struct ReplaysMenu;
+
+impl Menu for ReplaysMenu {}
+
+/// Watches the replay it holds when pressed
+#[derive(Debug, Component)]
+pub struct ReplayButton(Replay);
+
+#[derive(Debug, Component)]
+pub struct CloseReplaysButton;
+
+/// Number of replays listed, the most recent ones
+const LISTED_REPLAYS: usize = 8;
+
+const REPLAY_BUTTON_STYLE: Style = {
+    let mut style = Style::DEFAULT;
+    style.width = Val::Px(640.0);
+    style.height = Val::Px(56.0);
+    style.justify_content = JustifyContent::Center;
+    style.align_items = AlignItems::Center;
+    style
+};
+
+pub fn spawn_replays_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    commands
+        .spawn((ReplaysMenu, default_menu_backdrop()))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Replays",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 72.0,
+                    color: Color::BLACK,
+                },
+            ));
+
+            for replay in Replay::load_latest(LISTED_REPLAYS) {
+                let final_state = replay.game_after(replay.len());
+                let label = format!(
+                    "{}  {}x{} {:<7} {:>7} pts {:>5} moves",
+                    format_date(replay.date),
+                    replay.board.columns,
+                    replay.board.rows,
+                    replay.spawn_rules.preset_name().unwrap_or("Custom"),
+                    final_state.score(),
+                    replay.len(),
+                );
+                parent
+                    .spawn((
+                        ReplayButton(replay),
+                        ButtonBundle {
+                            background_color: Color::hex("8f7a66").unwrap().into(),
+                            style: REPLAY_BUTTON_STYLE,
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+
+            spawn_button(
+                parent,
+                &asset_server,
+                CloseReplaysButton,
+                "Back",
+                Color::hex("776e65").unwrap(),
+            );
+        });
+}
+
+pub fn open_replay(
+    mut commands: Commands,
+    query: Query<(&Interaction, &ReplayButton), Changed<Interaction>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, ReplayButton(replay)) in query.iter() {
+        if *interaction == Interaction::Pressed {
+            commands.insert_resource(Playback::new(replay.clone()));
+            app_state.set(AppState::Setup)
+        }
+    }
+}
+
+pub fn close_replays(
+    query: Query<&Interaction, (Changed<Interaction>, With<CloseReplaysButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::MainMenu)
+    }
+}
@@ -0,0 +1,521 @@
+use super::new_game_menu::{MAX_BOARD_SIDE, MIN_BOARD_SIDE};
+use crate::*;
+use bevy::prelude::*;
+
+#[derive(Debug, Component)]
+pub struct SettingsMenu;
+
+impl Menu for SettingsMenu {}
+
+/// Opens the settings, from the main menu or the pause menu
+#[derive(Debug, Component)]
+pub struct SettingsButton;
+
+#[derive(Debug, Component)]
+pub struct CloseSettingsButton;
+
+/// State the settings were opened from, which they go back to once closed
+#[derive(Debug, Resource)]
+pub struct SettingsOrigin(pub AppState);
+
+/// Binds the next key pressed to `action` once clicked, or unbinds it if it already was
+#[derive(Debug, Component)]
+pub struct BindButton {
+    action: Action,
+    state: BindState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindState {
+    Idle,
+    Listening,
+    /// The key pressed is bound to another action, it is taken from it if pressed again
+    Conflict(KeyCode),
+}
+
+impl BindButton {
+    fn spawn(parent: &mut ChildBuilder, font: &Handle<Font>, action: Action, keybinds: &Keybinds) {
+        let button = BindButton {
+            action,
+            state: BindState::Idle,
+        };
+        let label = button.label(keybinds);
+        parent
+            .spawn((
+                button,
+                ButtonBundle {
+                    background_color: Color::hex("bbada0").unwrap().into(),
+                    style: Style {
+                        width: Val::Px(216.0),
+                        ..SETTING_BUTTON_STYLE
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            });
+    }
+
+    fn label(&self, keybinds: &Keybinds) -> String {
+        let name = self.action.name();
+        match self.state {
+            BindState::Idle if keybinds.keys(self.action).is_empty() => format!("{name}: none"),
+            BindState::Idle => {
+                let keys: Vec<_> = keybinds
+                    .keys(self.action)
+                    .iter()
+                    .map(|key| format!("{key:?}"))
+                    .collect();
+                format!("{name}: {}", keys.join(", "))
+            }
+            BindState::Listening => format!("{name}: press a key"),
+            BindState::Conflict(key) => match keybinds.action(key) {
+                Some(other) => format!("{key:?} is used by {}, again?", other.name()),
+                None => format!("{name}: press a key"),
+            },
+        }
+    }
+}
+
+/// Cycles through the [`Keybinds::presets`]
+#[derive(Debug, Component)]
+pub struct KeybindPresetButton;
+
+impl KeybindPresetButton {
+    fn label(keybinds: &Keybinds) -> String {
+        format!("Keys: {}", keybinds.preset_name().unwrap_or("Custom"))
+    }
+}
+
+/// One of the [`Preferences`], attached to the text or button showing it
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    MusicVolume,
+    Columns,
+    Rows,
+    SwipeDistance,
+    SwipeTolerance,
+    AnimationDuration,
+    AiDepth,
+    AiTimeBudget,
+    Theme,
+    Fullscreen,
+}
+
+impl Setting {
+    fn label(&self, preferences: &Preferences) -> String {
+        match self {
+            Setting::MusicVolume => {
+                format!("Music: {}%", (preferences.music_volume * 100.0).round())
+            }
+            Setting::Columns => format!("Width: {}", preferences.board.columns),
+            Setting::Rows => format!("Height: {}", preferences.board.rows),
+            Setting::SwipeDistance => format!("Swipe: {}px", preferences.swipe_distance),
+            Setting::SwipeTolerance => format!("Swipe angle: {}°", preferences.swipe_tolerance),
+            Setting::AnimationDuration => format!(
+                "Animations: {}ms",
+                (preferences.animation_duration * 1000.0).round()
+            ),
+            Setting::AiDepth => format!("AI depth: {}", preferences.ai_depth),
+            Setting::AiTimeBudget => format!(
+                "AI time: {}ms",
+                (preferences.ai_time_budget * 1000.0).round()
+            ),
+            Setting::Theme => format!("Theme: {}", preferences.theme.name()),
+            Setting::Fullscreen if preferences.fullscreen => "Window: Fullscreen".to_string(),
+            Setting::Fullscreen => "Window: Windowed".to_string(),
+        }
+    }
+}
+
+/// Decreases or increases a numeric [`Setting`]
+#[derive(Debug, Component)]
+pub struct StepButton {
+    setting: Setting,
+    delta: i32,
+}
+
+impl StepButton {
+    fn spawn(parent: &mut ChildBuilder, font: &Handle<Font>, setting: Setting, delta: i32) {
+        parent
+            .spawn((
+                StepButton { setting, delta },
+                ButtonBundle {
+                    background_color: Color::hex("bbada0").unwrap().into(),
+                    style: Style {
+                        width: Val::Px(56.0),
+                        height: Val::Px(56.0),
+                        ..DEFAULT_BUTTON_STYLE
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    if delta < 0 { "-" } else { "+" },
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 32.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            });
+    }
+}
+
+/// Style of the settings' buttons, smaller than the default one so that they all fit on screen
+const SETTING_BUTTON_STYLE: Style = {
+    let mut style = DEFAULT_BUTTON_STYLE;
+    style.width = Val::Px(360.0);
+    style.height = Val::Px(56.0);
+    style
+};
+
+pub fn spawn_settings_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    keybinds: Res<Keybinds>,
+    preferences: Res<Preferences>,
+) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let column = || NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(8.0),
+            ..default()
+        },
+        ..default()
+    };
+
+    commands
+        .spawn((SettingsMenu, default_menu_backdrop()))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Settings",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 64.0,
+                    color: Color::BLACK,
+                },
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(32.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    //a button per action, showing the keys bound to it
+                    parent.spawn(column()).with_children(|parent| {
+                        spawn_button(
+                            parent,
+                            &asset_server,
+                            KeybindPresetButton,
+                            KeybindPresetButton::label(&keybinds),
+                            Color::hex("8f7a66").unwrap(),
+                        )
+                        .insert(Style {
+                            width: Val::Px(440.0),
+                            ..SETTING_BUTTON_STYLE
+                        });
+                        //two buttons per row, there are too many actions for a single column to fit
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(440.0),
+                                    flex_wrap: FlexWrap::Wrap,
+                                    justify_content: JustifyContent::SpaceBetween,
+                                    row_gap: Val::Px(8.0),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                for action in Action::ALL {
+                                    BindButton::spawn(parent, &font, action, &keybinds);
+                                }
+                            });
+                    });
+
+                    parent.spawn(column()).with_children(|parent| {
+                        //numeric settings, with buttons to decrease and increase them
+                        for setting in [
+                            Setting::MusicVolume,
+                            Setting::Columns,
+                            Setting::Rows,
+                            Setting::SwipeDistance,
+                            Setting::SwipeTolerance,
+                            Setting::AnimationDuration,
+                            Setting::AiDepth,
+                            Setting::AiTimeBudget,
+                        ] {
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        align_items: AlignItems::Center,
+                                        column_gap: Val::Px(16.0),
+                                        ..default()
+                                    },
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    StepButton::spawn(parent, &font, setting, -1);
+                                    parent.spawn((
+                                        setting,
+                                        TextBundle::from_section(
+                                            setting.label(&preferences),
+                                            TextStyle {
+                                                font: font.clone(),
+                                                font_size: 32.0,
+                                                color: Color::BLACK,
+                                            },
+                                        ),
+                                    ));
+                                    StepButton::spawn(parent, &font, setting, 1);
+                                });
+                        }
+
+                        //settings cycling through their values when clicked
+                        for setting in [Setting::Theme, Setting::Fullscreen] {
+                            spawn_button(
+                                parent,
+                                &asset_server,
+                                setting,
+                                setting.label(&preferences),
+                                Color::hex("bbada0").unwrap(),
+                            )
+                            .insert(SETTING_BUTTON_STYLE);
+                        }
+                    });
+                });
+
+            spawn_button(
+                parent,
+                &asset_server,
+                CloseSettingsButton,
+                "Back",
+                Color::hex("8f7a66").unwrap(),
+            );
+        });
+}
+
+pub fn open_settings(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<SettingsButton>)>,
+    current_state: Res<State<AppState>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        commands.insert_resource(SettingsOrigin(*current_state.get()));
+        app_state.set(AppState::SettingsMenu)
+    }
+}
+
+pub fn close_settings(
+    query: Query<&Interaction, (Changed<Interaction>, With<CloseSettingsButton>)>,
+    origin: Option<Res<SettingsOrigin>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(origin.map_or(AppState::MainMenu, |origin| origin.0))
+    }
+}
+
+/// Waits for a key to be pressed after a [`BindButton`] was clicked, and binds it to the button's action
+///
+/// Pressing a key already bound to the action unbinds it instead, while taking a key from another action must be
+/// confirmed by pressing it twice.
+pub fn rebind_key(
+    mut buttons: Query<(Ref<Interaction>, &mut BindButton, &mut BackgroundColor)>,
+    keys: Res<Input<KeyCode>>,
+    mut keybinds: ResMut<Keybinds>,
+) {
+    let clicked = buttons
+        .iter()
+        .find(|(interaction, ..)| interaction.is_changed() && **interaction == Interaction::Pressed)
+        .map(|(_, button, _)| button.action);
+    let key = keys.get_just_pressed().next().copied();
+
+    for (_, mut button, mut background) in &mut buttons {
+        let state = match (clicked, key, button.state) {
+            //clicking the button again cancels the binding, clicking another one moves to it
+            (Some(action), _, BindState::Idle) if action == button.action => BindState::Listening,
+            (Some(_), ..) => BindState::Idle,
+            (None, Some(key), BindState::Listening | BindState::Conflict(_)) => {
+                match keybinds.action(key) {
+                    Some(action) if action == button.action => {
+                        keybinds.unbind(action, key);
+                        BindState::Idle
+                    }
+                    Some(_) if button.state != BindState::Conflict(key) => BindState::Conflict(key),
+                    _ => {
+                        keybinds.bind(button.action, key);
+                        BindState::Idle
+                    }
+                }
+            }
+            (None, _, state) => state,
+        };
+        if button.state == state {
+            continue;
+        }
+        button.state = state;
+        *background = if state == BindState::Idle {
+            Color::hex("bbada0").unwrap().into()
+        } else {
+            Color::hex("8f7a66").unwrap().into()
+        };
+    }
+}
+
+pub fn cycle_keybind_preset(
+    query: Query<&Interaction, (Changed<Interaction>, With<KeybindPresetButton>)>,
+    mut keybinds: ResMut<Keybinds>,
+) {
+    let Ok(Interaction::Pressed) = query.get_single() else {
+        return;
+    };
+    let presets = Keybinds::presets();
+    //custom bindings are followed by the first preset
+    let next = presets
+        .iter()
+        .position(|(_, preset)| *preset == *keybinds)
+        .map_or(0, |current| (current + 1) % presets.len());
+    *keybinds = presets[next].1.clone();
+}
+
+/// Shows the keys bound to each action, and the state of the buttons being rebound
+pub fn update_keybind_labels(
+    buttons: Query<(Ref<BindButton>, &Children)>,
+    presets: Query<&Children, With<KeybindPresetButton>>,
+    mut texts: Query<&mut Text>,
+    keybinds: Res<Keybinds>,
+) {
+    let mut relabel = |children: &Children, label: String| {
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    };
+    for (button, children) in &buttons {
+        if keybinds.is_changed() || button.is_changed() {
+            relabel(children, button.label(&keybinds));
+        }
+    }
+    if keybinds.is_changed() {
+        for children in &presets {
+            relabel(children, KeybindPresetButton::label(&keybinds));
+        }
+    }
+}
+
+pub fn step_setting(
+    query: Query<(&Interaction, &StepButton), Changed<Interaction>>,
+    mut preferences: ResMut<Preferences>,
+) {
+    for (interaction, button) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button.setting {
+            Setting::MusicVolume => {
+                //in steps of 10%, rounded so that they don't drift
+                let volume = preferences.music_volume * 10.0 + button.delta as f32;
+                preferences.music_volume = volume.round().clamp(0.0, 10.0) / 10.0;
+            }
+            Setting::Columns | Setting::Rows => {
+                let side = if button.setting == Setting::Columns {
+                    &mut preferences.board.columns
+                } else {
+                    &mut preferences.board.rows
+                };
+                *side = side
+                    .saturating_add_signed(button.delta as isize)
+                    .clamp(MIN_BOARD_SIDE, MAX_BOARD_SIDE);
+            }
+            Setting::SwipeDistance => {
+                preferences.swipe_distance =
+                    (preferences.swipe_distance + 10.0 * button.delta as f32).clamp(10.0, 300.0)
+            }
+            Setting::SwipeTolerance => {
+                preferences.swipe_tolerance =
+                    (preferences.swipe_tolerance + 5.0 * button.delta as f32).clamp(5.0, 45.0)
+            }
+            Setting::AnimationDuration => {
+                //in steps of 50ms, rounded so that they don't drift
+                let duration = preferences.animation_duration * 20.0 + button.delta as f32;
+                preferences.animation_duration = duration.round().clamp(0.0, 10.0) / 20.0;
+            }
+            Setting::AiDepth => {
+                preferences.ai_depth = preferences
+                    .ai_depth
+                    .saturating_add_signed(button.delta)
+                    .clamp(1, 8)
+            }
+            Setting::AiTimeBudget => {
+                //in steps of 50ms, rounded so that they don't drift
+                let budget = preferences.ai_time_budget * 20.0 + button.delta as f32;
+                preferences.ai_time_budget = budget.round().clamp(1.0, 20.0) / 20.0;
+            }
+            Setting::Theme | Setting::Fullscreen => (),
+        }
+    }
+}
+
+pub fn cycle_setting(
+    query: Query<(&Interaction, &Setting), (Changed<Interaction>, With<Button>)>,
+    mut preferences: ResMut<Preferences>,
+) {
+    for (interaction, setting) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match setting {
+            Setting::Theme => {
+                let current = Theme::ALL
+                    .iter()
+                    .position(|theme| *theme == preferences.theme)
+                    .unwrap_or_default();
+                preferences.theme = Theme::ALL[(current + 1) % Theme::ALL.len()];
+            }
+            Setting::Fullscreen => preferences.fullscreen = !preferences.fullscreen,
+            Setting::MusicVolume
+            | Setting::Columns
+            | Setting::Rows
+            | Setting::SwipeDistance
+            | Setting::SwipeTolerance
+            | Setting::AnimationDuration
+            | Setting::AiDepth
+            | Setting::AiTimeBudget => (),
+        }
+    }
+}
+
+pub fn update_setting_labels(
+    labels: Query<(Entity, &Setting, Option<&Children>)>,
+    mut texts: Query<&mut Text>,
+    preferences: Res<Preferences>,
+) {
+    for (entity, setting, children) in &labels {
+        //buttons hold their text in a child while labels are texts themselves
+        let children = children.into_iter().flatten().copied();
+        for entity in std::iter::once(entity).chain(children) {
+            if let Ok(mut text) = texts.get_mut(entity) {
+                text.sections[0].value = setting.label(&preferences);
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::*;
+use bevy::prelude::*;
+
+#[derive(Debug, Component)]
+pub struct StatsMenu;
+
+impl Menu for StatsMenu {}
+
+/// Opens the [`StatsMenu`] from the main menu
+#[derive(Debug, Component)]
+pub struct StatsButton;
+
+#[derive(Debug, Component)]
+pub struct CloseStatsButton;
+
+pub fn spawn_stats_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    statistics: Res<Statistics>,
+) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let text = |value: String, font_size: f32| {
+        TextSection::new(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::BLACK,
+            },
+        )
+    };
+    commands
+        .spawn((StatsMenu, default_menu_backdrop()))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_sections([text(
+                "Statistics".to_string(),
+                90.0,
+            )]));
+
+            if statistics.games().is_empty() {
+                parent.spawn(TextBundle::from_sections([text(
+                    "Finish a game to see your statistics".to_string(),
+                    32.0,
+                )]));
+            } else {
+                //a column per kind of statistics, each starting with its title
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(48.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (title, lines) in columns(&statistics) {
+                            parent.spawn(TextBundle::from_sections([
+                                text(format!("{title}\n"), 32.0),
+                                text(lines.join("\n"), 24.0),
+                            ]));
+                        }
+                    });
+            }
+
+            spawn_button(
+                parent,
+                &asset_server,
+                CloseStatsButton,
+                "Back",
+                Color::hex("776e65").unwrap(),
+            );
+        });
+}
+
+/// Title and lines of each column of the menu
+fn columns(statistics: &Statistics) -> [(&'static str, Vec<String>); 3] {
+    let games = statistics.games().len();
+    let mut overall = vec![
+        format!("Games played: {games}"),
+        format!("Best score: {}", statistics.best_score()),
+        format!("Average score: {:.0}", statistics.average_score()),
+        format!("Average moves: {:.0}", statistics.average_moves()),
+        format!("Hints used: {}", statistics.hints()),
+        String::new(),
+        "Win rate".to_string(),
+    ];
+    for (power, (won, played)) in statistics.wins() {
+        overall.push(format!(
+            "{:>5}: {won}/{played} ({:.0}%)",
+            2u32.pow(power),
+            100.0 * won as f32 / played as f32
+        ));
+    }
+
+    let total_moves: u32 = statistics.directions().iter().map(|(_, count)| count).sum();
+    let mut moves: Vec<_> = statistics
+        .directions()
+        .into_iter()
+        .map(|(direction, count)| {
+            format!(
+                "{:<5}: {:.0}%",
+                format!("{direction:?}"),
+                100.0 * count as f32 / total_moves.max(1) as f32
+            )
+        })
+        .collect();
+    moves.push(String::new());
+    moves.push("Merges".to_string());
+    for (value, count) in statistics.merges() {
+        moves.push(format!("{value:>5}: {count}"));
+    }
+
+    let largest = statistics
+        .highest_powers()
+        .into_iter()
+        .rev()
+        .map(|(power, count)| {
+            format!(
+                "{:>5}: {count} ({:.0}%)",
+                2u32.pow(power),
+                100.0 * count as f32 / games as f32
+            )
+        })
+        .collect();
+
+    [
+        ("Overall", overall),
+        ("Largest tile", largest),
+        ("Directions", moves),
+    ]
+}
+
+pub fn open_stats(
+    query: Query<&Interaction, (Changed<Interaction>, With<StatsButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::StatsMenu)
+    }
+}
+
+pub fn close_stats(
+    query: Query<&Interaction, (Changed<Interaction>, With<CloseStatsButton>)>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Ok(Interaction::Pressed) = query.get_single() {
+        app_state.set(AppState::MainMenu)
+    }
+}
@@ -6,7 +6,20 @@ pub struct WonMenu;
 
 impl Menu for WonMenu {}
 
-pub fn spawn_won_menu(mut commands: Commands, asset_server: Res<AssetServer>, score: Res<Score>) {
+pub fn spawn_won_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    game: Res<GameState>,
+    history: Res<History>,
+    new_best: Res<NewPersonalBest>,
+) {
+    let assisted = match (history.undos_used(), history.ai_moves()) {
+        (0, 0) => String::new(),
+        (undos, 0) => format!(" (assisted, {undos} undos)"),
+        (0, ai_moves) => format!(" (assisted, {ai_moves} AI moves)"),
+        (undos, ai_moves) => format!(" (assisted, {undos} undos, {ai_moves} AI moves)"),
+    };
     let font = asset_server.load("fonts/FiraMono-Medium.ttf");
     commands
         .spawn((WonMenu, default_menu_backdrop()))
@@ -23,13 +36,33 @@ pub fn spawn_won_menu(mut commands: Commands, asset_server: Res<AssetServer>, sc
                             },
                         ),
                         TextSection::new(
-                            format!("You won with a score of {}!", score.0),
+                            format!("You won with a score of {}!{assisted}", score.0),
                             TextStyle {
-                                font,
+                                font: font.clone(),
                                 font_size: 60.0,
                                 color: Color::GOLD,
                             },
                         ),
+                        TextSection::new(
+                            if new_best.0 {
+                                "\nNew personal best!"
+                            } else {
+                                ""
+                            },
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 48.0,
+                                color: Color::hex("f65e3b").unwrap(),
+                            },
+                        ),
+                        TextSection::new(
+                            format!("\nSeed: {}", game.seed()),
+                            TextStyle {
+                                font,
+                                font_size: 32.0,
+                                color: Color::BLACK,
+                            },
+                        ),
                     ],
                     alignment: TextAlignment::Center,
                     ..default()
@@ -0,0 +1,152 @@
+use b2048::{
+    engine::{Board, Direction, GameState, TileMerge, TileSlide},
+    Expectimax,
+};
+
+/// A game on a board of `columns`x`rows` holding the given (`index`, `power`) tiles
+fn game(columns: usize, rows: usize, tiles: &[(usize, usize)]) -> GameState {
+    let mut game = GameState::with_seed(Board { columns, rows }, 0);
+    for &(index, power) in tiles {
+        game.place(index, power);
+    }
+    game
+}
+
+fn tiles(game: &GameState) -> Vec<(usize, usize)> {
+    game.tiles().collect()
+}
+
+#[test]
+fn wide_board_moves_along_rows_and_columns() {
+    //5 columns, 3 rows: index 4 is the end of the first row
+    let mut wide = game(5, 3, &[(4, 1)]);
+
+    assert!(wide.apply(Direction::Left).changed);
+    assert_eq!(tiles(&wide), [(0, 1)]);
+
+    assert!(wide.apply(Direction::Down).changed);
+    assert_eq!(tiles(&wide), [(10, 1)]);
+
+    assert!(wide.apply(Direction::Right).changed);
+    assert_eq!(tiles(&wide), [(14, 1)]);
+
+    assert!(wide.apply(Direction::Up).changed);
+    assert_eq!(tiles(&wide), [(4, 1)]);
+}
+
+#[test]
+fn tall_board_moves_along_rows_and_columns() {
+    //3 columns, 6 rows: index 17 is the end of the last row
+    let mut tall = game(3, 6, &[(2, 1)]);
+
+    assert!(tall.apply(Direction::Down).changed);
+    assert_eq!(tiles(&tall), [(17, 1)]);
+
+    assert!(tall.apply(Direction::Left).changed);
+    assert_eq!(tiles(&tall), [(15, 1)]);
+
+    assert!(tall.apply(Direction::Up).changed);
+    assert_eq!(tiles(&tall), [(0, 1)]);
+
+    assert!(tall.apply(Direction::Right).changed);
+    assert_eq!(tiles(&tall), [(2, 1)]);
+}
+
+#[test]
+fn merges_follow_the_board_shape() {
+    //two 2s in the last column of a 3x6 board, two 4s in the last row of a 5x3 board
+    let mut tall = game(3, 6, &[(2, 1), (17, 1)]);
+    let outcome = tall.apply(Direction::Up);
+    assert_eq!(outcome.merged, [1]);
+    assert_eq!(tiles(&tall), [(2, 2)]);
+
+    let mut wide = game(5, 3, &[(10, 2), (14, 2)]);
+    let outcome = wide.apply(Direction::Right);
+    assert_eq!(outcome.merged, [2]);
+    assert_eq!(tiles(&wide), [(14, 3)]);
+}
+
+#[test]
+fn stale_detection_on_non_square_boards() {
+    for (columns, rows) in [(5, 3), (3, 6)] {
+        //a checkerboard of 2s and 4s can't be moved
+        let checkerboard: Vec<_> = (0..columns * rows)
+            .map(|idx| (idx, 1 + (idx / columns + idx % columns) % 2))
+            .collect();
+        let stale = game(columns, rows, &checkerboard);
+        assert!(stale.legal_moves().is_empty(), "{columns}x{rows}");
+        assert!(stale.is_over(), "{columns}x{rows}");
+
+        //making the last two tiles of the last column 8s allows vertical moves only
+        let mut last_column = checkerboard.clone();
+        last_column[columns * rows - 1].1 = 3;
+        last_column[columns * (rows - 1) - 1].1 = 3;
+        let playable = game(columns, rows, &last_column);
+        assert_eq!(
+            playable.legal_moves(),
+            [Direction::Up, Direction::Down],
+            "{columns}x{rows}"
+        );
+        assert!(!playable.is_over(), "{columns}x{rows}");
+    }
+}
+
+#[test]
+fn seeded_games_play_out_identically() {
+    let play = || {
+        let mut game = GameState::with_seed(
+            Board {
+                columns: 5,
+                rows: 3,
+            },
+            2048,
+        );
+        game.start();
+        for direction in Direction::ALL.iter().cycle().take(40) {
+            game.play(*direction);
+        }
+        game
+    };
+    assert_eq!(play().cells(), play().cells());
+}
+
+#[test]
+fn moves_report_where_each_tile_went() {
+    //a row of 2, 2, 4 on a 4x4 board, moved right
+    let mut board = game(4, 4, &[(0, 1), (1, 1), (2, 2)]);
+    let outcome = board.apply(Direction::Right);
+
+    assert_eq!(tiles(&board), [(2, 2), (3, 2)]);
+    assert_eq!(
+        outcome.slides,
+        [
+            TileSlide { from: 2, to: 3 },
+            TileSlide { from: 1, to: 2 },
+            TileSlide { from: 0, to: 2 },
+        ]
+    );
+    assert_eq!(
+        outcome.merges,
+        [TileMerge {
+            kept: 1,
+            absorbed: 0,
+            into: 2,
+            power: 1
+        }]
+    );
+}
+
+#[test]
+fn expectimax_only_picks_moves_changing_the_board() {
+    //only the empty bottom right cell lets tiles move, to the right or down
+    let layout = [1, 2, 1, 2, 1, 2, 1, 2];
+    let open = game(3, 3, &layout.into_iter().enumerate().collect::<Vec<_>>());
+    let search = Expectimax::default();
+    let best = search.best_move(&open).expect("the board can still move");
+    assert!([Direction::Right, Direction::Down].contains(&best));
+    assert_eq!(search.evaluate(&open).len(), 2);
+
+    let mut stale = open.clone();
+    stale.place(8, 3);
+    assert_eq!(search.best_move(&stale), None);
+}
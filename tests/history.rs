@@ -0,0 +1,59 @@
+use b2048::{
+    engine::{Board, GameState},
+    Congratulation, History, Score, Snapshot, UndoRules,
+};
+
+fn snapshot() -> Snapshot {
+    Snapshot {
+        game: GameState::new(Board::default()),
+        score: Score(0),
+        congratulation: Congratulation::default(),
+    }
+}
+
+#[test]
+fn undo_limit_counts_every_undo() {
+    let limited = UndoRules { limit: Some(2) };
+    let unlimited = UndoRules::default();
+    let mut history = History::default();
+    for _ in 0..4 {
+        history.record(snapshot());
+    }
+
+    for _ in 0..2 {
+        assert!(limited.allows(&history));
+        assert!(history.undo(snapshot()).is_some());
+    }
+    assert!(!limited.allows(&history));
+    assert!(unlimited.allows(&history));
+
+    //redoing doesn't give undos back
+    assert!(history.redo(snapshot()).is_some());
+    assert!(!limited.allows(&history));
+    assert_eq!(history.undos_used(), 2);
+}
+
+#[test]
+fn no_undo_allowed_with_a_limit_of_zero() {
+    let none = UndoRules { limit: Some(0) };
+    let mut history = History::default();
+    history.record(snapshot());
+    assert!(!none.allows(&history));
+}
+
+#[test]
+fn history_forgets_the_oldest_moves_past_its_capacity() {
+    let mut history = History::default();
+    history.capacity = 2;
+    for _ in 0..3 {
+        history.record(snapshot());
+    }
+    assert!(history.undo(snapshot()).is_some());
+    assert!(history.undo(snapshot()).is_some());
+    assert!(history.undo(snapshot()).is_none());
+
+    let mut empty = History::default();
+    empty.capacity = 0;
+    empty.record(snapshot());
+    assert!(empty.undo(snapshot()).is_none());
+}
@@ -0,0 +1,43 @@
+use b2048::{
+    engine::{Board, Direction, GameState, SpawnRules},
+    Replay,
+};
+
+#[test]
+fn replays_play_out_like_the_recorded_game() {
+    let board = Board {
+        columns: 4,
+        rows: 5,
+    };
+    let mut game = GameState::with_seed(board.clone(), 2048);
+    game.start();
+    let mut replay = Replay::new(game.seed(), board, SpawnRules::default(), Some(3));
+    //only the moves changing the board are recorded
+    for direction in Direction::ALL.iter().cycle().take(40) {
+        if game.play(*direction).changed {
+            replay.push(*direction, 100);
+        }
+    }
+    let halfway = replay.len() / 2;
+
+    //the moves are saved as one letter each
+    let saved = ron::to_string(&replay).unwrap();
+    assert!(saved.contains("directions:\"L"), "{saved}");
+    let loaded: Replay = ron::from_str(&saved).unwrap();
+    assert_eq!(loaded, replay);
+    assert_eq!(loaded.len(), game.moves() as usize);
+
+    let replayed = loaded.game_after(loaded.len());
+    assert_eq!(replayed.cells(), game.cells());
+    assert_eq!(replayed.score(), game.score());
+
+    //seeking back rebuilds the game as it was after those moves
+    let mut partial = GameState::with_seed(game.board().clone(), game.seed());
+    partial.start();
+    for nth in 0..halfway {
+        let (delay, direction) = loaded.get(nth).unwrap();
+        assert_eq!(delay, 100);
+        partial.play(direction);
+    }
+    assert_eq!(loaded.game_after(halfway).cells(), partial.cells());
+}
@@ -0,0 +1,57 @@
+use b2048::{
+    engine::{Board, Direction, GameState},
+    Congratulation, SavedGame, WinTarget,
+};
+
+fn saved_game() -> SavedGame {
+    let mut game = GameState::with_seed(Board::default(), 4096);
+    game.start();
+    for direction in Direction::ALL.iter().cycle().take(20) {
+        game.play(*direction);
+    }
+    SavedGame {
+        score: game.score(),
+        game,
+        congratulation: Congratulation::default(),
+        target: WinTarget {
+            power: 12,
+            every_milestone: false,
+        },
+        replay: None,
+        undos_used: 2,
+        ai_moves: 3,
+        hints_used: 1,
+    }
+}
+
+#[test]
+fn saved_games_continue_where_they_were_left() {
+    let saved = saved_game();
+    let loaded: SavedGame = ron::from_str(&ron::to_string(&saved).unwrap()).unwrap();
+    assert_eq!(loaded.validate(), Ok(()));
+    assert_eq!(loaded.game.cells(), saved.game.cells());
+    assert_eq!(loaded.game.moves(), saved.game.moves());
+    assert_eq!(loaded.score, saved.score);
+    assert_eq!(loaded.target, saved.target);
+    assert_eq!(
+        (loaded.undos_used, loaded.ai_moves, loaded.hints_used),
+        (2, 3, 1)
+    );
+
+    //the random generator is saved too, so the tiles spawned next are the same
+    let (mut continued, mut original) = (loaded.game, saved.game);
+    for direction in Direction::ALL.iter().rev().cycle().take(20) {
+        continued.play(*direction);
+        original.play(*direction);
+    }
+    assert_eq!(continued.cells(), original.cells());
+}
+
+#[test]
+fn saves_not_matching_their_board_are_rejected() {
+    let saved = ron::to_string(&saved_game()).unwrap();
+    let resized = saved.replace("columns:4", "columns:5");
+    assert_ne!(resized, saved);
+    let loaded: SavedGame = ron::from_str(&resized).unwrap();
+    assert!(loaded.validate().is_err());
+}
@@ -0,0 +1,117 @@
+use b2048::{
+    engine::{Board, GameState, SpawnRules},
+    BestScores, Congratulation, FinishGame, History, Leaderboard, LeaderboardEntry, Score,
+    Snapshot,
+};
+use std::time::Duration;
+
+/// A game scoring `score` on a board of `columns`x`columns`, played with `rules`
+fn entry(score: u32, columns: usize, rules: &SpawnRules) -> LeaderboardEntry {
+    LeaderboardEntry {
+        score,
+        largest_tile: 256,
+        moves: 200,
+        duration: Duration::from_secs(60),
+        date: score as u64,
+        seed: score as u64,
+        board: Board {
+            columns,
+            rows: columns,
+        },
+        rules: rules.clone(),
+        hints: 0,
+    }
+}
+
+fn scores(leaderboard: &Leaderboard) -> Vec<u32> {
+    leaderboard
+        .entries()
+        .iter()
+        .map(|entry| entry.score)
+        .collect()
+}
+
+#[test]
+fn best_scores_are_kept_per_board() {
+    let small = Board {
+        columns: 3,
+        rows: 3,
+    };
+    let mut best_scores = BestScores::default();
+    assert_eq!(best_scores.get(&small), 0);
+    assert!(best_scores.submit(&small, 500));
+    //a tie doesn't beat the best score
+    assert!(!best_scores.submit(&small, 500));
+    assert!(!best_scores.submit(&small, 400));
+    assert!(best_scores.submit(&Board::default(), 100));
+    assert_eq!(best_scores.get(&small), 500);
+    assert_eq!(best_scores.get(&Board::default()), 100);
+}
+
+#[test]
+fn leaderboard_keeps_the_best_games_of_each_board_and_rules() {
+    let classic = SpawnRules::default();
+    let [(_, relaxed), ..] = SpawnRules::presets();
+    assert_ne!(relaxed, classic);
+
+    let mut leaderboard = Leaderboard::default();
+    for score in 1..=Leaderboard::KEPT as u32 {
+        assert!(leaderboard.submit(entry(score * 100, 4, &classic)));
+    }
+    assert!(!leaderboard.submit(entry(50, 4, &classic)));
+    //the lowest game is pushed out by a better one
+    assert!(leaderboard.submit(entry(150, 4, &classic)));
+    assert_eq!(scores(&leaderboard)[Leaderboard::KEPT - 2..], [200, 150]);
+
+    //other boards and rules have their own games
+    assert!(leaderboard.submit(entry(10, 5, &classic)));
+    assert!(leaderboard.submit(entry(10, 4, &relaxed)));
+    assert_eq!(leaderboard.entries().len(), Leaderboard::KEPT + 2);
+}
+
+#[test]
+fn leaderboard_ties_rank_after_earlier_games() {
+    let rules = SpawnRules::default();
+    let mut leaderboard = Leaderboard::default();
+    for _ in 0..Leaderboard::KEPT {
+        assert!(leaderboard.submit(entry(100, 4, &rules)));
+    }
+    //a full leaderboard isn't entered by tying its lowest game
+    assert!(!leaderboard.submit(entry(100, 4, &rules)));
+
+    let mut first = entry(300, 4, &rules);
+    first.seed = 1;
+    let mut second = entry(300, 4, &rules);
+    second.seed = 2;
+    assert!(leaderboard.submit(first));
+    assert!(leaderboard.submit(second));
+    let seeds: Vec<_> = leaderboard.entries()[..2]
+        .iter()
+        .map(|entry| entry.seed)
+        .collect();
+    assert_eq!(seeds, [1, 2]);
+}
+
+#[test]
+fn only_games_over_and_unassisted_count() {
+    assert!(FinishGame::GameOver.is_over());
+    assert!(FinishGame::Restart.is_over());
+    //a game that was quit can be continued
+    assert!(!FinishGame::Quit.is_over());
+
+    let snapshot = || Snapshot {
+        game: GameState::new(Board::default()),
+        score: Score(0),
+        congratulation: Congratulation::default(),
+    };
+    let mut history = History::default();
+    history.record(snapshot());
+    history.record_hint();
+    assert!(!history.is_assisted());
+    assert!(history.undo(snapshot()).is_some());
+    assert!(history.is_assisted());
+
+    let mut played_by_ai = History::default();
+    played_by_ai.record_ai_move();
+    assert!(played_by_ai.is_assisted());
+}
@@ -0,0 +1,30 @@
+use b2048::{Keybinds, Preferences, SettingsFile};
+use bevy::input::keyboard::KeyCode;
+
+#[test]
+fn settings_of_another_version_are_ignored() {
+    let older: SettingsFile = ron::from_str(
+        "(version: 1, keybinds: (undo: [U]), preferences: (music_volume: 0.3, swipe_distance: 120.0))",
+    )
+    .unwrap();
+    let checked = older.checked();
+    assert_eq!(checked.keybinds, Keybinds::default());
+    assert_eq!(checked.preferences, Preferences::default());
+}
+
+#[test]
+fn missing_settings_take_their_default_value() {
+    let current: SettingsFile = ron::from_str(&format!(
+        "(version: {}, keybinds: (undo: [U]), preferences: (music_volume: 0.3))",
+        SettingsFile::VERSION
+    ))
+    .unwrap();
+    let checked = current.checked();
+    assert_eq!(checked.keybinds.undo, vec![KeyCode::U]);
+    assert_eq!(checked.keybinds.redo, Keybinds::default().redo);
+    assert_eq!(checked.preferences.music_volume, 0.3);
+    assert_eq!(
+        checked.preferences.swipe_distance,
+        Preferences::default().swipe_distance
+    );
+}
@@ -0,0 +1,70 @@
+use b2048::engine::{Board, GameState, SpawnRules};
+
+fn board(columns: usize, rows: usize) -> Board {
+    Board { columns, rows }
+}
+
+#[test]
+fn presets_are_valid() {
+    for (name, rules) in SpawnRules::presets() {
+        assert_eq!(rules.validate(&board(3, 3)), Ok(()), "{name}");
+    }
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    let rules = |values: Vec<(usize, usize)>, starting_tiles| SpawnRules {
+        values,
+        starting_tiles,
+        tiles_per_move: 1,
+    };
+    assert!(rules(vec![], 2).validate(&board(4, 4)).is_err());
+    assert!(rules(vec![(0, 1), (1, 9)], 2)
+        .validate(&board(4, 4))
+        .is_err());
+    assert!(rules(vec![(1, 0), (2, 0)], 2)
+        .validate(&board(4, 4))
+        .is_err());
+    //weights of 0 are fine as long as one tile can spawn
+    assert!(rules(vec![(1, 0), (2, 1)], 2)
+        .validate(&board(4, 4))
+        .is_ok());
+    assert!(rules(vec![(1, 1)], 6).validate(&board(2, 3)).is_ok());
+    assert!(rules(vec![(1, 1)], 7).validate(&board(2, 3)).is_err());
+}
+
+#[test]
+fn spawn_counts_follow_the_rules() {
+    let hectic = SpawnRules {
+        values: vec![(1, 1)],
+        starting_tiles: 3,
+        tiles_per_move: 2,
+    };
+    let mut game = GameState::with_seed(board(4, 4), 7).with_rules(hectic);
+    game.start();
+    assert_eq!(game.tiles().count(), 3);
+
+    //merged tiles leave room for the ones spawned after the move
+    let before = game.tiles().count();
+    let outcome = game.play(game.legal_moves()[0]);
+    assert_eq!(game.tiles().count(), before - outcome.merged.len() + 2);
+}
+
+#[test]
+fn spawned_powers_follow_the_weights() {
+    let rules = SpawnRules {
+        values: vec![(1, 3), (2, 0), (5, 1)],
+        ..SpawnRules::default()
+    };
+    let mut game = GameState::with_seed(board(4, 4), 42).with_rules(rules);
+    let mut counts = [0; 6];
+    for _ in 0..4000 {
+        counts[game.random_power()] += 1;
+    }
+    assert_eq!(counts[0] + counts[3] + counts[4], 0);
+    //a weight of 0 never spawns
+    assert_eq!(counts[2], 0);
+    //3 to 1 odds, within a few percent
+    assert!((2900..3100).contains(&counts[1]), "{counts:?}");
+    assert_eq!(counts[1] + counts[5], 4000);
+}
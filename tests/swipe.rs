@@ -0,0 +1,48 @@
+use b2048::{engine::Direction, swipe_direction};
+use bevy::math::Vec2;
+
+const MIN_DISTANCE: f32 = 40.0;
+const TOLERANCE: f32 = 30.0;
+
+fn swipe(x: f32, y: f32) -> Option<Direction> {
+    swipe_direction(
+        Vec2::new(100.0, 100.0),
+        Vec2::new(100.0 + x, 100.0 + y),
+        MIN_DISTANCE,
+        TOLERANCE,
+    )
+}
+
+#[test]
+fn swipes_follow_window_coordinates() {
+    assert_eq!(swipe(-80.0, 0.0), Some(Direction::Left));
+    assert_eq!(swipe(80.0, 0.0), Some(Direction::Right));
+    //window coordinates grow downwards
+    assert_eq!(swipe(0.0, -80.0), Some(Direction::Up));
+    assert_eq!(swipe(0.0, 80.0), Some(Direction::Down));
+}
+
+#[test]
+fn short_swipes_are_ignored() {
+    assert_eq!(swipe(39.0, 0.0), None);
+    assert_eq!(swipe(0.0, -39.0), None);
+    //the distance is measured along the swipe, not along its direction
+    assert_eq!(swipe(30.0, 30.0), None);
+    assert_eq!(swipe(40.0, 0.0), Some(Direction::Right));
+}
+
+#[test]
+fn diagonal_swipes_are_ignored() {
+    let at = |degrees: f32| {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        swipe(100.0 * cos, 100.0 * sin)
+    };
+    assert_eq!(at(29.0), Some(Direction::Right));
+    assert_eq!(at(-29.0), Some(Direction::Right));
+    assert_eq!(at(31.0), None);
+    assert_eq!(at(45.0), None);
+    assert_eq!(at(59.0), None);
+    assert_eq!(at(61.0), Some(Direction::Down));
+    assert_eq!(at(151.0), Some(Direction::Left));
+    assert_eq!(at(-119.0), Some(Direction::Up));
+}